@@ -0,0 +1,140 @@
+use crate::Error;
+use crate::config::ConnectOptions;
+use crate::socket::Socket;
+use crate::tls::TlsConnect;
+use std::io::{Read, Write};
+
+// CancelRequest code: 1234 in the high 16 bits and 5678 in the low 16 bits
+const CANCEL_REQUEST_CODE: i32 = 80877102;
+
+/// A token to cancel or terminate a running replication session from another thread.
+///
+/// Obtained with `Connection::cancel_token` before the connection is moved into a
+/// `Subscriber`, and cheap to clone:
+///
+/// ```rust,no_run
+/// use lolrepl::{ConnectOptions, NoTls, Subscriber};
+///
+/// # fn main() -> Result<(), lolrepl::Error> {
+/// let options: ConnectOptions = "postgresql://replication_user@localhost/mydb".parse()?;
+/// let connection = options.connect(NoTls)?;
+/// let cancel_token = connection.cancel_token();
+///
+/// std::thread::spawn(move || {
+///     std::thread::sleep(std::time::Duration::from_secs(60));
+///     cancel_token.cancel()
+/// });
+///
+/// let mut subscriber = Subscriber::new(connection, "my_slot", "my_publication")?;
+/// loop {
+///     // Fails once the replication command is canceled
+///     let message = subscriber.next()?;
+///     println!("{:?}", message);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    options: ConnectOptions,
+    process_id: i32,
+    secret_key: Vec<u8>,
+}
+
+impl CancelToken {
+    // Create a token for the backend identified by its key data
+    pub(crate) fn new(options: ConnectOptions, process_id: i32, secret_key: Vec<u8>) -> Self {
+        CancelToken {
+            options,
+            process_id,
+            secret_key,
+        }
+    }
+
+    /// Get the process ID of the backend this token cancels.
+    pub fn process_id(&self) -> i32 {
+        self.process_id
+    }
+
+    /// Ask the server to cancel the command the backend is running.
+    ///
+    /// Opens a fresh socket to the server and sends a CancelRequest. The server does not
+    /// answer; whether the command was canceled shows on the original connection,
+    /// which receives an error with SQLSTATE 57014.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating success or an `Error` on failure, e.g.
+    /// `Error::CancelHostUnknown` if the connection was made over a caller-supplied stream
+    /// without a host in its options.
+    pub fn cancel(&self) -> Result<(), Error> {
+        self.check_host()?;
+        let socket = Socket::connect(&self.options)?;
+        self.cancel_with(socket)
+    }
+
+    /// Send the CancelRequest over a stream the caller opened to the server.
+    ///
+    /// Useful when the original connection was made over a stream `ConnectOptions`
+    /// cannot open itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A fresh, unencrypted stream to the server
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating success or an `Error` on failure.
+    pub fn cancel_with<S: Read + Write>(&self, mut stream: S) -> Result<(), Error> {
+        // CancelRequest: length, request code, process ID and secret key, no message type byte
+        let length = 12 + self.secret_key.len();
+        let mut request = Vec::with_capacity(length);
+        request.extend_from_slice(&(length as i32).to_be_bytes());
+        request.extend_from_slice(&CANCEL_REQUEST_CODE.to_be_bytes());
+        request.extend_from_slice(&self.process_id.to_be_bytes());
+        request.extend_from_slice(&self.secret_key);
+        stream.write_all(&request)?;
+        stream.flush()?;
+
+        // The server closes the connection once it handled the request. Waiting for
+        // that makes sure the request was delivered before the caller moves on.
+        let mut buffer = [0u8; 1];
+        while matches!(stream.read(&mut buffer), Ok(n) if n > 0) {}
+
+        Ok(())
+    }
+
+    /// Ask the server to terminate the backend, e.g. a walsender that ignores cancellation.
+    ///
+    /// Opens a separate, non-replication connection with the same options and runs
+    /// `pg_terminate_backend`, which requires the user to be a superuser, the owner of
+    /// the backend or a member of `pg_signal_backend`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tls` - The TLS backend for the separate connection, or `NoTls`
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing whether the backend was signalled, or an `Error` on failure.
+    pub fn terminate<C: TlsConnect<Socket> + Clone>(&self, tls: C) -> Result<bool, Error> {
+        self.check_host()?;
+        let mut options = self.options.clone();
+        options.replication = false;
+
+        let mut connection = options.connect(tls)?;
+        let rows =
+            connection.query(&format!("SELECT pg_terminate_backend({})", self.process_id))?;
+
+        Ok(rows.first().and_then(|row| row.first()) == Some(&Some("t".to_string())))
+    }
+
+    // Refuse to guess the server of a connection made over a caller-supplied stream,
+    // connecting to the default localhost:5432 might reach a different server
+    fn check_host(&self) -> Result<(), Error> {
+        if self.options.hosts.is_empty() {
+            return Err(Error::CancelHostUnknown);
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,18 @@
 use crate::Error;
-use md5;
+use crate::cancel::CancelToken;
+use crate::config::{ConnectOptions, ProtocolVersion, TargetSessionAttrs};
+use crate::error::DbError;
+use crate::event::{self, Event};
+use crate::scram::{SCRAM_SHA_256, SCRAM_SHA_256_PLUS, ScramChannelBinding, ScramSha256};
+use crate::tls::{
+    ChannelBinding, ChannelBindingMode, MaybeTlsStream, TlsConnect, TlsStream, negotiate_tls,
+};
+use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::sync::Arc;
+
+// Session parameters sent in the startup message unless set in the options
+const DEFAULT_PARAMS: [(&str, &str); 2] = [("DateStyle", "ISO"), ("client_encoding", "UTF8")];
 
 // PostgreSQL message representation
 pub(crate) struct PgMessage {
@@ -15,6 +27,12 @@ pub(crate) struct PgMessage {
 /// writing PostgreSQL protocol messages in the context of replication.
 pub struct Connection<T: Read + Write> {
     stream: T,
+    options: ConnectOptions,
+    parameters: HashMap<String, String>,
+    process_id: i32,
+    secret_key: Vec<u8>,
+    protocol_version: ProtocolVersion,
+    rejected_protocol_options: Vec<String>,
 }
 
 impl<T: Read + Write> Connection<T> {
@@ -23,6 +41,10 @@ impl<T: Read + Write> Connection<T> {
     /// This method establishes a replication connection by sending startup messages,
     /// handling authentication, and preparing the connection for replication operations.
     ///
+    /// The connection does not know the address of the server, so `CancelToken::cancel`
+    /// fails on its tokens; send the request with `CancelToken::cancel_with` over a new
+    /// stream instead, or use `Connection::with_options` with a host.
+    ///
     /// # Arguments
     ///
     /// * `stream` - A stream that implements Read + Write (typically a TCP connection)
@@ -34,28 +56,89 @@ impl<T: Read + Write> Connection<T> {
     ///
     /// Returns a `Result` containing the `Connection` on success, or an `Error` on failure.
     pub fn new(stream: T, user: &str, password: &str, database: &str) -> Result<Self, Error> {
-        let mut connection = Connection { stream };
+        let options = ConnectOptions::new()
+            .user(user)
+            .password(password)
+            .database(database);
+
+        Self::with_options(stream, &options)
+    }
+
+    /// Create a new replication connection with an existing stream and connection options.
+    ///
+    /// The stream is treated as unencrypted, so no channel binding is available. Use
+    /// `Connection::with_tls_options` for a stream that is already encrypted with TLS.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A stream that implements Read + Write (typically a TCP connection)
+    /// * `options` - The options to connect with
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Connection` on success, or an `Error` on failure.
+    pub fn with_options(stream: T, options: &ConnectOptions) -> Result<Self, Error> {
+        Self::start(stream, options, ChannelBinding::none())
+    }
+
+    // Run the startup and authentication flow over the stream
+    fn start(
+        stream: T,
+        options: &ConnectOptions,
+        channel_binding: ChannelBinding,
+    ) -> Result<Self, Error> {
+        let mut connection = Connection {
+            stream,
+            options: options.clone(),
+            parameters: HashMap::new(),
+            process_id: 0,
+            secret_key: Vec::new(),
+            protocol_version: options.max_protocol_version,
+            rejected_protocol_options: Vec::new(),
+        };
 
-        connection.send_startup_message(user, database)?;
-        connection.handle_authentication(user, password)?;
+        connection.send_startup_message(options)?;
+        connection.handle_authentication(options, &channel_binding, false)?;
         connection.process_startup_messages()?;
 
         Ok(connection)
     }
 
     // Send startup message with replication flag
-    fn send_startup_message(&mut self, user: &str, database: &str) -> Result<(), Error> {
-        // Protocol version (3.0)
-        let protocol_version: i32 = 196608; // 3.0 in format: 3 << 16 | 0
+    fn send_startup_message(&mut self, options: &ConnectOptions) -> Result<(), Error> {
+        // Protocol version, major in the high 16 bits and minor in the low 16 bits
+        let protocol_version: i32 = 3 << 16 | options.max_protocol_version.minor();
 
         // Build the startup packet
         let mut packet = Vec::new();
         packet.extend_from_slice(&protocol_version.to_be_bytes());
 
         // Add parameters
-        self.write_string_param(&mut packet, "user", user)?;
-        self.write_string_param(&mut packet, "database", database)?;
-        self.write_string_param(&mut packet, "replication", "database")?;
+        self.write_string_param(&mut packet, "user", &options.user)?;
+        self.write_string_param(&mut packet, "database", &options.database)?;
+        if options.replication {
+            self.write_string_param(&mut packet, "replication", "database")?;
+        }
+        if let Some(application_name) = &options.application_name {
+            self.write_string_param(&mut packet, "application_name", application_name)?;
+        }
+        if let Some(server_options) = &options.options {
+            self.write_string_param(&mut packet, "options", server_options)?;
+        }
+
+        // Ask for the formats the value parsers expect, unless the caller chose otherwise
+        for (name, value) in DEFAULT_PARAMS {
+            if !options
+                .params
+                .iter()
+                .any(|(param, _)| param.eq_ignore_ascii_case(name))
+            {
+                self.write_string_param(&mut packet, name, value)?;
+            }
+        }
+        for (name, value) in &options.params {
+            self.write_string_param(&mut packet, name, value)?;
+        }
 
         // Add terminating null byte
         packet.push(0);
@@ -87,8 +170,14 @@ impl<T: Read + Write> Connection<T> {
     }
 
     // Handle authentication flow
-    fn handle_authentication(&mut self, user: &str, password: &str) -> Result<(), Error> {
+    fn handle_authentication(
+        &mut self,
+        options: &ConnectOptions,
+        channel_binding: &ChannelBinding,
+        channel_bound: bool,
+    ) -> Result<(), Error> {
         let message = self.read_message(false)?;
+        let require_binding = options.channel_binding == ChannelBindingMode::Require;
 
         match message.message_type {
             b'R' => {
@@ -104,16 +193,25 @@ impl<T: Read + Write> Connection<T> {
                     message.data[3],
                 ]);
 
+                // Only SASL can bind the authentication to the TLS session
+                if require_binding && !channel_bound && auth_type != 10 {
+                    return Err(Error::Authentication(
+                        "Channel binding required, but server authenticated client without channel binding"
+                            .to_string(),
+                    ));
+                }
+
                 match auth_type {
                     0 => {
                         // AuthenticationOk - no password needed
                     }
                     3 => {
                         // ClearTextPassword
+                        let password = Self::required_password(options)?;
                         self.send_password_message(password)?;
 
                         // Wait for AuthenticationOk
-                        self.handle_authentication(user, password)?;
+                        self.handle_authentication(options, channel_binding, channel_bound)?;
                     }
                     5 => {
                         // MD5Password
@@ -123,12 +221,21 @@ impl<T: Read + Write> Connection<T> {
 
                         let salt = &message.data[4..8];
 
-                        // Use the stored username from the connection
-                        let md5_password = self.compute_md5_password(password, user, salt)?;
+                        let password = Self::required_password(options)?;
+                        let md5_password =
+                            self.compute_md5_password(password, &options.user, salt)?;
                         self.send_password_message(&md5_password)?;
 
                         // Wait for AuthenticationOk
-                        self.handle_authentication(user, password)?;
+                        self.handle_authentication(options, channel_binding, channel_bound)?;
+                    }
+                    10 => {
+                        // SASL, the payload is a list of mechanism names
+                        let channel_bound =
+                            self.authenticate_sasl(&message.data[4..], options, channel_binding)?;
+
+                        // Wait for AuthenticationOk
+                        self.handle_authentication(options, channel_binding, channel_bound)?;
                     }
                     _ => {
                         return Err(Error::Authentication(format!(
@@ -138,10 +245,17 @@ impl<T: Read + Write> Connection<T> {
                     }
                 }
             }
+            b'v' => {
+                // NegotiateProtocolVersion, the server does not support the requested
+                // minor version or some protocol options and continues without them
+                self.handle_negotiate_protocol_version(&message.data)?;
+
+                // Authentication follows
+                self.handle_authentication(options, channel_binding, channel_bound)?;
+            }
             b'E' => {
                 // ErrorResponse
-                let error_message = self.parse_error_message(&message.data)?;
-                return Err(Error::Authentication(error_message));
+                return Err(DbError::parse(&message.data)?.into());
             }
             _ => {
                 // Unexpected message
@@ -155,6 +269,63 @@ impl<T: Read + Write> Connection<T> {
         Ok(())
     }
 
+    // Record the protocol version and options the server settled on
+    fn handle_negotiate_protocol_version(&mut self, data: &[u8]) -> Result<(), Error> {
+        if data.len() < 8 {
+            return Err(Error::UnexpectedEndOfData("NegotiateProtocolVersion"));
+        }
+
+        // The newest version the server supports, with the major version in the high 16 bits
+        let newest_version = i32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        let option_count = i32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+
+        if newest_version >> 16 != 3 {
+            return Err(Error::ReplicationProtocolViolation(format!(
+                "Server negotiated protocol version {}.{}",
+                newest_version >> 16,
+                newest_version & 0xffff
+            )));
+        }
+
+        // The server may only downgrade the minor version
+        self.protocol_version = match newest_version & 0xffff {
+            minor if minor > self.protocol_version.minor() => {
+                return Err(Error::ReplicationProtocolViolation(format!(
+                    "Server negotiated protocol version 3.{}, but 3.{} was requested",
+                    minor,
+                    self.protocol_version.minor()
+                )));
+            }
+            0 => ProtocolVersion::V3_0,
+            2 => ProtocolVersion::V3_2,
+            minor => {
+                return Err(Error::ReplicationProtocolViolation(format!(
+                    "Unsupported protocol version 3.{}",
+                    minor
+                )));
+            }
+        };
+
+        let mut options = data[8..].split(|b| *b == 0);
+        self.rejected_protocol_options = (0..option_count)
+            .map(|_| {
+                let option = options.next().ok_or(Error::UnexpectedEndOfData(
+                    "NegotiateProtocolVersion option",
+                ))?;
+                Ok(std::str::from_utf8(option)?.to_string())
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(())
+    }
+
+    // Get the password for a server that asked for one
+    fn required_password(options: &ConnectOptions) -> Result<&str, Error> {
+        options.password.as_deref().ok_or_else(|| {
+            Error::Authentication("Server requested a password, but none was supplied".to_string())
+        })
+    }
+
     // Send password message
     fn send_password_message(&mut self, password: &str) -> Result<(), Error> {
         let mut password_data = Vec::new();
@@ -165,6 +336,118 @@ impl<T: Read + Write> Connection<T> {
         Ok(())
     }
 
+    // Run a SCRAM-SHA-256 exchange in response to AuthenticationSASL, returning
+    // whether the authentication was bound to the TLS session
+    fn authenticate_sasl(
+        &mut self,
+        mechanisms: &[u8],
+        options: &ConnectOptions,
+        channel_binding: &ChannelBinding,
+    ) -> Result<bool, Error> {
+        let mut offers_scram = false;
+        let mut offers_scram_plus = false;
+
+        for mechanism in mechanisms
+            .split(|b| *b == 0)
+            .take_while(|mechanism| !mechanism.is_empty())
+        {
+            if mechanism == SCRAM_SHA_256.as_bytes() {
+                offers_scram = true;
+            } else if mechanism == SCRAM_SHA_256_PLUS.as_bytes() {
+                offers_scram_plus = true;
+            }
+        }
+
+        // The certificate hash is only usable if the policy allows channel binding
+        let certificate_hash = match options.channel_binding {
+            ChannelBindingMode::Disable => None,
+            _ => channel_binding.tls_server_end_point.clone(),
+        };
+
+        let (mechanism, scram_channel_binding) = match certificate_hash {
+            Some(hash) if offers_scram_plus => (
+                SCRAM_SHA_256_PLUS,
+                ScramChannelBinding::TlsServerEndPoint(hash),
+            ),
+            _ if options.channel_binding == ChannelBindingMode::Require => {
+                return Err(Error::Authentication(
+                    "Channel binding required, but not supported by the server or the connection"
+                        .to_string(),
+                ));
+            }
+            Some(_) if offers_scram => (SCRAM_SHA_256, ScramChannelBinding::NotOffered),
+            None if offers_scram => (SCRAM_SHA_256, ScramChannelBinding::Unsupported),
+            _ => {
+                return Err(Error::Authentication(
+                    "Server does not offer a supported SASL mechanism".to_string(),
+                ));
+            }
+        };
+
+        let channel_bound = mechanism == SCRAM_SHA_256_PLUS;
+        let password = Self::required_password(options)?;
+        let mut scram = ScramSha256::new(password, scram_channel_binding);
+
+        // SASLInitialResponse: mechanism name, then length-prefixed client-first-message
+        let client_first = scram.client_first_message();
+        let mut data = Vec::new();
+        data.extend_from_slice(mechanism.as_bytes());
+        data.push(0); // null terminator
+        data.extend_from_slice(&(client_first.len() as i32).to_be_bytes());
+        data.extend_from_slice(client_first.as_bytes());
+        self.write_message(b'p', &data, false)?;
+
+        // AuthenticationSASLContinue carries the server-first-message
+        let server_first = self.read_sasl_message(11)?;
+        let client_final = scram.client_final_message(&server_first)?;
+
+        // SASLResponse: the client-final-message, no length prefix
+        self.write_message(b'p', client_final.as_bytes(), false)?;
+
+        // AuthenticationSASLFinal carries the server signature
+        let server_final = self.read_sasl_message(12)?;
+        scram.verify_server_final(&server_final)?;
+
+        Ok(channel_bound)
+    }
+
+    // Read an authentication message of the given type during a SASL exchange
+    fn read_sasl_message(&mut self, expected_auth_type: i32) -> Result<Vec<u8>, Error> {
+        let message = self.read_message(false)?;
+
+        match message.message_type {
+            b'R' => {
+                if message.data.len() < 4 {
+                    return Err(Error::InvalidAuthRequest);
+                }
+
+                let auth_type = i32::from_be_bytes([
+                    message.data[0],
+                    message.data[1],
+                    message.data[2],
+                    message.data[3],
+                ]);
+
+                if auth_type != expected_auth_type {
+                    return Err(Error::ReplicationProtocolViolation(format!(
+                        "Unexpected authentication request during SASL exchange: {}",
+                        auth_type
+                    )));
+                }
+
+                Ok(message.data[4..].to_vec())
+            }
+            b'E' => {
+                // ErrorResponse, e.g. a wrong password
+                Err(DbError::parse(&message.data)?.into())
+            }
+            _ => Err(Error::ReplicationProtocolViolation(format!(
+                "Unexpected message type during authentication: {}",
+                message.message_type as char
+            ))),
+        }
+    }
+
     // Compute MD5 password hash
     fn compute_md5_password(
         &self,
@@ -192,38 +475,6 @@ impl<T: Read + Write> Connection<T> {
         Ok(result)
     }
 
-    // Parse error message
-    fn parse_error_message(&self, data: &[u8]) -> Result<String, Error> {
-        let mut error_message = String::new();
-        let mut i = 0;
-
-        while i < data.len() {
-            let field_type = data[i];
-            i += 1;
-
-            if field_type == 0 {
-                break; // End of message
-            }
-
-            let mut field_value = Vec::new();
-            while i < data.len() && data[i] != 0 {
-                field_value.push(data[i]);
-                i += 1;
-            }
-            i += 1; // Skip null terminator
-
-            if let Ok(value) = std::str::from_utf8(&field_value) {
-                match field_type {
-                    b'M' => error_message = value.to_string(), // Message
-                    b'S' => error_message = format!("{}: {}", value, error_message), // Severity
-                    _ => {}                                    // Ignore other fields
-                }
-            }
-        }
-
-        Ok(error_message)
-    }
-
     // Process startup messages until ready
     fn process_startup_messages(&mut self) -> Result<(), Error> {
         let mut ready_for_query = false;
@@ -234,24 +485,22 @@ impl<T: Read + Write> Connection<T> {
             match message.message_type {
                 b'S' => {
                     // ParameterStatus message
-                    // Process parameter=value pairs if needed
+                    self.handle_parameter_status(&message.data)?;
                 }
                 b'K' => {
-                    // BackendKeyData message
-                    if message.data.len() >= 8 {
-                        let _process_id = i32::from_be_bytes([
-                            message.data[0],
-                            message.data[1],
-                            message.data[2],
-                            message.data[3],
-                        ]);
-                        let _secret_key = i32::from_be_bytes([
-                            message.data[4],
-                            message.data[5],
-                            message.data[6],
-                            message.data[7],
-                        ]);
+                    // BackendKeyData message, the secret key is 4 bytes before protocol 3.2
+                    // and up to 256 bytes since
+                    if message.data.len() < 8 || message.data.len() > 4 + 256 {
+                        return Err(Error::BackendKeyDataInvalid);
                     }
+
+                    self.process_id = i32::from_be_bytes([
+                        message.data[0],
+                        message.data[1],
+                        message.data[2],
+                        message.data[3],
+                    ]);
+                    self.secret_key = message.data[4..].to_vec();
                 }
                 b'Z' => {
                     // ReadyForQuery message
@@ -259,19 +508,15 @@ impl<T: Read + Write> Connection<T> {
                 }
                 b'E' => {
                     // ErrorResponse
-                    let error_message = self.parse_error_message(&message.data)?;
-                    return Err(Error::ServerStartupFailure(error_message));
+                    return Err(DbError::parse(&message.data)?.into());
                 }
                 b'N' => {
                     // NoticeResponse
-                    let notice = self.parse_error_message(&message.data)?;
-                    eprintln!("Notice: {}", notice);
+                    let notice = DbError::parse(&message.data)?;
+                    self.emit(Event::Notice(Box::new(notice)));
                 }
                 _ => {
-                    eprintln!(
-                        "Unhandled message type during startup: {}",
-                        message.message_type as char
-                    );
+                    self.emit(Event::UnknownMessage(message.message_type));
                 }
             }
         }
@@ -279,6 +524,176 @@ impl<T: Read + Write> Connection<T> {
         Ok(())
     }
 
+    /// Get a token that can cancel the running command of this connection from elsewhere.
+    ///
+    /// The token connects to the host and port of the options this connection was
+    /// opened with. For a connection over a caller-supplied stream without a host in the
+    /// options, only `CancelToken::cancel_with` can send the request.
+    pub fn cancel_token(&self) -> CancelToken {
+        CancelToken::new(
+            self.options.clone(),
+            self.process_id,
+            self.secret_key.clone(),
+        )
+    }
+
+    /// Get the current value of a server parameter reported with ParameterStatus.
+    ///
+    /// The server reports parameters such as `server_version`, `server_encoding`,
+    /// `TimeZone`, `DateStyle`, `integer_datetimes` and `IntervalStyle` during startup,
+    /// and again whenever they change.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The parameter name, e.g. `server_version`
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing the value if the server reported the parameter.
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters.get(name).map(String::as_str)
+    }
+
+    /// Get all server parameters reported with ParameterStatus.
+    pub fn parameters(&self) -> &HashMap<String, String> {
+        &self.parameters
+    }
+
+    /// Get the server version in the numeric form of `server_version_num`, e.g. `150004`
+    /// for 15.4.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing the version if the server reported a `server_version`
+    /// that could be parsed.
+    pub fn server_version_num(&self) -> Option<u32> {
+        parse_server_version(self.parameter("server_version")?)
+    }
+
+    /// Set the handler for notices and other events, replacing the one from the options.
+    pub fn set_event_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(Event) + Send + Sync + 'static,
+    {
+        self.options.event_handler = Some(Arc::new(handler));
+    }
+
+    /// Pass an event to the event handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to pass on
+    pub(crate) fn emit(&self, event: Event) {
+        event::emit(self.options.event_handler.as_ref(), event);
+    }
+
+    /// Check whether the server matches the target session attributes.
+    ///
+    /// Uses the `in_hot_standby` and `default_transaction_read_only` parameters if the
+    /// server reports them, and queries the server otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_session_attrs` - The kind of server that is acceptable
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing whether the server matches, or an `Error` on failure.
+    pub(crate) fn matches_session_attrs(
+        &mut self,
+        target_session_attrs: TargetSessionAttrs,
+    ) -> Result<bool, Error> {
+        match target_session_attrs {
+            TargetSessionAttrs::Any | TargetSessionAttrs::PreferStandby => Ok(true),
+            TargetSessionAttrs::ReadWrite => Ok(!self.is_read_only()?),
+            TargetSessionAttrs::ReadOnly => self.is_read_only(),
+            TargetSessionAttrs::Primary => Ok(!self.is_hot_standby()?),
+            TargetSessionAttrs::Standby => self.is_hot_standby(),
+        }
+    }
+
+    // Whether the server is a standby, from ParameterStatus or by asking it
+    fn is_hot_standby(&mut self) -> Result<bool, Error> {
+        if let Some(in_hot_standby) = self.parameter("in_hot_standby") {
+            return Ok(in_hot_standby == "on");
+        }
+
+        let rows = self.query("SELECT pg_catalog.pg_is_in_recovery()")?;
+        Ok(first_value(&rows) == Some("t"))
+    }
+
+    // Whether sessions are read-only by default, from ParameterStatus or by asking the server
+    fn is_read_only(&mut self) -> Result<bool, Error> {
+        if let (Some(in_hot_standby), Some(read_only)) = (
+            self.parameter("in_hot_standby"),
+            self.parameter("default_transaction_read_only"),
+        ) {
+            return Ok(in_hot_standby == "on" || read_only == "on");
+        }
+
+        let rows = self.query("SHOW transaction_read_only")?;
+        Ok(first_value(&rows) == Some("on"))
+    }
+
+    /// Get the process ID of the backend serving this connection.
+    pub fn process_id(&self) -> i32 {
+        self.process_id
+    }
+
+    /// Get the secret key of the backend, needed to cancel its queries.
+    pub fn secret_key(&self) -> &[u8] {
+        &self.secret_key
+    }
+
+    /// Get the protocol version in use, after negotiation with the server.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+
+    /// Get the `_pq_.` protocol options the server did not recognize.
+    pub fn rejected_protocol_options(&self) -> &[String] {
+        &self.rejected_protocol_options
+    }
+
+    /// Record a ParameterStatus message, received during startup or at any later time.
+    ///
+    /// Fails if the new value is a setting the value parsers do not support.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The ParameterStatus payload, a name and a value as null-terminated strings
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating success or an `Error` on failure.
+    pub(crate) fn handle_parameter_status(&mut self, data: &[u8]) -> Result<(), Error> {
+        let mut fields = data.split(|b| *b == 0);
+        let (Some(name), Some(value)) = (fields.next(), fields.next()) else {
+            return Err(Error::ParameterStatusInvalid);
+        };
+
+        let name = std::str::from_utf8(name)?;
+        let value = std::str::from_utf8(value)?;
+
+        check_parameter(name, value)?;
+        let previous = self.parameters.insert(name.to_string(), value.to_string());
+
+        // Values reported during startup are not changes
+        if previous.is_some_and(|previous| previous != value) {
+            self.emit(Event::ParameterStatus {
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    // The underlying stream, e.g. to change its read timeout
+    pub(crate) fn stream(&self) -> &T {
+        &self.stream
+    }
+
     /// Read a PostgreSQL protocol message from the connection.
     ///
     /// This method reads a complete PostgreSQL message from the underlying stream.
@@ -364,3 +779,253 @@ impl<T: Read + Write> Connection<T> {
         Ok(())
     }
 }
+
+impl<T: TlsStream> Connection<T> {
+    /// Create a new replication connection over a stream that is already encrypted with TLS.
+    ///
+    /// The stream's channel binding data is used for `SCRAM-SHA-256-PLUS` according
+    /// to the channel binding policy in `options`.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A TLS stream that implements `TlsStream`
+    /// * `options` - The options to connect with
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Connection` on success, or an `Error` on failure.
+    pub fn with_tls_options(stream: T, options: &ConnectOptions) -> Result<Self, Error> {
+        let channel_binding = stream.channel_binding();
+        Self::start(stream, options, channel_binding)
+    }
+}
+
+impl<S: Read + Write, T: TlsStream> Connection<MaybeTlsStream<S, T>> {
+    /// Create a new replication connection, negotiating TLS before the startup message.
+    ///
+    /// Depending on the SSL mode in `options`, an SSLRequest is sent on the stream and,
+    /// if the server accepts it, the stream is upgraded with the given TLS backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - An unencrypted stream that implements Read + Write
+    /// * `options` - The options to connect with
+    /// * `tls` - The TLS backend, or `NoTls` to never use TLS
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Connection` on success, or an `Error` on failure.
+    pub fn with_tls<C>(stream: S, options: &ConnectOptions, tls: C) -> Result<Self, Error>
+    where
+        C: TlsConnect<S, Stream = T>,
+    {
+        let stream = negotiate_tls(stream, options, tls)?;
+        Self::with_tls_options(stream, options)
+    }
+}
+
+// Get the first value of the first row of a query result
+fn first_value(rows: &[Vec<Option<String>>]) -> Option<&str> {
+    rows.first()?.first()?.as_deref()
+}
+
+// Convert a server_version such as "15.4", "16beta1" or "9.6.24 (Debian)" to server_version_num
+fn parse_server_version(version: &str) -> Option<u32> {
+    let end = version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(version.len());
+    let mut parts = version[..end].split('.').map(|part| part.parse::<u32>());
+
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().and_then(Result::ok).unwrap_or(0);
+
+    // Before PostgreSQL 10 the first two parts together were the major version
+    if major >= 10 {
+        Some(major * 10000 + minor)
+    } else {
+        let patch = parts.next().and_then(Result::ok).unwrap_or(0);
+        Some(major * 10000 + minor * 100 + patch)
+    }
+}
+
+// Reject server settings that change the text format of values in ways the parsers cannot handle
+fn check_parameter(name: &str, value: &str) -> Result<(), Error> {
+    let supported = match name {
+        // Dates are parsed in ISO format, the day/month order only matters for other styles
+        "DateStyle" => value.starts_with("ISO"),
+        // Binary timestamps are parsed as 64-bit integers
+        "integer_datetimes" => value == "on",
+        _ => true,
+    };
+
+    if supported {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedServerSetting(format!(
+            "{} = {}",
+            name, value
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::Mutex;
+
+    // A stream that replays scripted server messages and discards what the client sends
+    struct ScriptedStream {
+        input: Cursor<Vec<u8>>,
+    }
+
+    impl ScriptedStream {
+        fn new(messages: &[(u8, &[u8])]) -> Self {
+            let mut input = Vec::new();
+            for (message_type, data) in messages {
+                input.push(*message_type);
+                input.extend_from_slice(&((data.len() + 4) as i32).to_be_bytes());
+                input.extend_from_slice(data);
+            }
+
+            ScriptedStream {
+                input: Cursor::new(input),
+            }
+        }
+    }
+
+    impl Read for ScriptedStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for ScriptedStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_server_version() {
+        assert_eq!(parse_server_version("15.4"), Some(150004));
+        assert_eq!(
+            parse_server_version("17.0 (Debian 17.0-1.pgdg120+1)"),
+            Some(170000)
+        );
+        assert_eq!(parse_server_version("16beta1"), Some(160000));
+        assert_eq!(parse_server_version("9.6.24"), Some(90624));
+        assert_eq!(parse_server_version("devel"), None);
+    }
+
+    #[test]
+    fn test_negotiate_protocol_version() {
+        let mut negotiate = Vec::new();
+        negotiate.extend_from_slice(&(3i32 << 16).to_be_bytes());
+        negotiate.extend_from_slice(&1i32.to_be_bytes());
+        negotiate.extend_from_slice(b"_pq_.unknown\0");
+
+        let mut key_data = 42i32.to_be_bytes().to_vec();
+        key_data.extend_from_slice(&[7; 32]);
+
+        let stream = ScriptedStream::new(&[
+            (b'v', &negotiate),
+            (b'R', &0i32.to_be_bytes()),
+            (b'K', &key_data),
+            (b'Z', b"I"),
+        ]);
+
+        let options = ConnectOptions::new()
+            .user("postgres")
+            .max_protocol_version(ProtocolVersion::V3_2);
+        let connection = Connection::with_options(stream, &options).unwrap();
+
+        assert_eq!(connection.protocol_version(), ProtocolVersion::V3_0);
+        assert_eq!(connection.rejected_protocol_options(), ["_pq_.unknown"]);
+        assert_eq!(connection.process_id(), 42);
+        assert_eq!(connection.secret_key(), [7; 32]);
+
+        // A server may not upgrade the requested version
+        let mut negotiate = Vec::new();
+        negotiate.extend_from_slice(&(3i32 << 16 | 2).to_be_bytes());
+        negotiate.extend_from_slice(&0i32.to_be_bytes());
+
+        let stream = ScriptedStream::new(&[(b'v', &negotiate)]);
+        let options = ConnectOptions::new().user("postgres");
+        assert!(matches!(
+            Connection::with_options(stream, &options),
+            Err(Error::ReplicationProtocolViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_startup_events() {
+        let stream = ScriptedStream::new(&[
+            (b'R', &0i32.to_be_bytes()),
+            (b'S', b"TimeZone\0UTC\0"),
+            (b'N', b"SWARNING\0VWARNING\0C01000\0Mcareful\0\0"),
+            (b'?', b""),
+            (b'K', &[0, 0, 0, 42, 0, 0, 0, 7]),
+            (b'Z', b"I"),
+        ]);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let options = ConnectOptions::new().user("postgres").event_handler({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event)
+        });
+
+        let mut connection = Connection::with_options(stream, &options).unwrap();
+
+        assert_eq!(connection.parameter("TimeZone"), Some("UTC"));
+        assert_eq!(connection.process_id(), 42);
+        assert_eq!(connection.secret_key(), [0, 0, 0, 7]);
+
+        // The options have no host, the token cannot guess the server
+        assert!(matches!(
+            connection.cancel_token().cancel(),
+            Err(Error::CancelHostUnknown)
+        ));
+
+        // Reporting the same value again is not a change
+        connection
+            .handle_parameter_status(b"TimeZone\0UTC\0")
+            .unwrap();
+        connection
+            .handle_parameter_status(b"TimeZone\0Asia/Kolkata\0")
+            .unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], Event::Notice(notice) if notice.code == "01000"));
+        assert_eq!(events[1], Event::UnknownMessage(b'?'));
+        assert_eq!(
+            events[2],
+            Event::ParameterStatus {
+                name: "TimeZone".to_string(),
+                value: "Asia/Kolkata".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_check_parameter() {
+        assert!(check_parameter("DateStyle", "ISO, MDY").is_ok());
+        assert!(check_parameter("DateStyle", "ISO, DMY").is_ok());
+        assert!(check_parameter("integer_datetimes", "on").is_ok());
+        assert!(check_parameter("TimeZone", "Asia/Kolkata").is_ok());
+
+        assert!(matches!(
+            check_parameter("DateStyle", "SQL, DMY"),
+            Err(Error::UnsupportedServerSetting(_))
+        ));
+        assert!(matches!(
+            check_parameter("integer_datetimes", "off"),
+            Err(Error::UnsupportedServerSetting(_))
+        ));
+    }
+}
@@ -0,0 +1,367 @@
+use crate::Error;
+use crate::event::{self, Event, EventHandler};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// The Unix socket directory libpq uses when no host is given, which .pgpass entries for
+// localhost also cover
+const DEFAULT_SOCKET_DIR: &str = "/tmp";
+
+// libpq environment variables and the connection parameters they set
+const ENVIRONMENT_PARAMS: &[(&str, &str)] = &[
+    ("PGHOST", "host"),
+    ("PGPORT", "port"),
+    ("PGUSER", "user"),
+    ("PGPASSWORD", "password"),
+    ("PGDATABASE", "dbname"),
+    ("PGAPPNAME", "application_name"),
+    ("PGOPTIONS", "options"),
+    ("PGSSLMODE", "sslmode"),
+    ("PGCHANNELBINDING", "channel_binding"),
+    ("PGCONNECT_TIMEOUT", "connect_timeout"),
+    ("PGTARGETSESSIONATTRS", "target_session_attrs"),
+];
+
+// Collect the connection parameters set through environment variables
+pub(crate) fn environment_params() -> Vec<(String, String)> {
+    ENVIRONMENT_PARAMS
+        .iter()
+        .filter_map(|(variable, key)| {
+            std::env::var(variable)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| (key.to_string(), value))
+        })
+        .collect()
+}
+
+// Get the name of the operating system user, the default PostgreSQL user
+pub(crate) fn os_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|user| !user.is_empty())
+}
+
+// Look up the parameters of a named service, first in the user's service file
+// (PGSERVICEFILE or ~/.pg_service.conf), then in PGSYSCONFDIR/pg_service.conf
+pub(crate) fn service_params(name: &str) -> Result<Vec<(String, String)>, Error> {
+    let user_file = std::env::var_os("PGSERVICEFILE")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".pg_service.conf")));
+    let system_file = std::env::var_os("PGSYSCONFDIR")
+        .map(|directory| PathBuf::from(directory).join("pg_service.conf"));
+
+    for path in user_file.iter().chain(system_file.iter()) {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(Error::Io(err)),
+        };
+
+        // The first file that defines the service wins, definitions are not merged
+        if let Some(params) = parse_service_file(&contents, name)? {
+            return Ok(params);
+        }
+    }
+
+    Err(Error::ServiceNotFound(name.to_string()))
+}
+
+// Find the parameters of a service in the contents of a pg_service.conf file
+fn parse_service_file(contents: &str, name: &str) -> Result<Option<Vec<(String, String)>>, Error> {
+    let mut params = None;
+
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix('[') {
+            // A later section ends the service we were collecting
+            if params.is_some() {
+                break;
+            }
+            if section.strip_suffix(']') == Some(name) {
+                params = Some(Vec::new());
+            }
+            continue;
+        }
+
+        if let Some(params) = params.as_mut() {
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                Error::InvalidConnectionString(format!(
+                    "syntax error in service file, line {}",
+                    number + 1
+                ))
+            })?;
+            params.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    Ok(params)
+}
+
+// Get the location of the password file, PGPASSFILE or the platform default
+pub(crate) fn passfile_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("PGPASSFILE") {
+        return Some(PathBuf::from(path));
+    }
+
+    if cfg!(windows) {
+        std::env::var_os("APPDATA").map(|appdata| {
+            PathBuf::from(appdata)
+                .join("postgresql")
+                .join("pgpass.conf")
+        })
+    } else {
+        home_dir().map(|home| home.join(".pgpass"))
+    }
+}
+
+// Look up a password in a .pgpass file, ignoring files that are missing or
+// readable by group or others
+pub(crate) fn pgpass_password(
+    path: &Path,
+    host: &str,
+    port: u16,
+    database: &str,
+    user: &str,
+    event_handler: Option<&EventHandler>,
+) -> Result<Option<String>, Error> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(Error::Io(err)),
+    };
+
+    if !metadata.is_file() {
+        return Ok(None);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if metadata.permissions().mode() & 0o077 != 0 {
+            event::emit(
+                event_handler,
+                Event::Warning(format!(
+                    "Password file \"{}\" has group or world access; permissions should be u=rw (0600) or less",
+                    path.display()
+                )),
+            );
+            return Ok(None);
+        }
+    }
+
+    let contents = fs::read_to_string(path)?;
+
+    Ok(find_password(
+        &contents,
+        host,
+        &port.to_string(),
+        database,
+        user,
+    ))
+}
+
+// Find the password of the first hostname:port:database:username:password line that matches
+fn find_password(
+    contents: &str,
+    host: &str,
+    port: &str,
+    database: &str,
+    user: &str,
+) -> Option<String> {
+    // Like libpq, a missing host or the default socket directory matches localhost, and
+    // a missing database the user's database
+    let host = if host.is_empty() || host == DEFAULT_SOCKET_DIR {
+        "localhost"
+    } else {
+        host
+    };
+    let database = if database.is_empty() { user } else { database };
+
+    for line in contents.lines() {
+        let line = line.trim_end_matches('\r');
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields = split_pgpass_line(line);
+        if fields.len() < 5 {
+            continue;
+        }
+
+        let matches = [host, port, database, user]
+            .iter()
+            .zip(&fields)
+            .all(|(value, (field, wildcard))| *wildcard || field == value);
+
+        if matches {
+            return Some(fields[4].0.clone());
+        }
+    }
+
+    None
+}
+
+// Split a .pgpass line on unescaped colons, unescaping each field and noting
+// which fields are a bare "*" wildcard
+fn split_pgpass_line(line: &str) -> Vec<(String, bool)> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut raw_wildcard = true;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ':' => {
+                let wildcard = raw_wildcard && field == "*";
+                fields.push((std::mem::take(&mut field), wildcard));
+                raw_wildcard = true;
+            }
+            '\\' => {
+                // An escaped character is always literal, even an escaped "*"
+                raw_wildcard = false;
+                field.push(chars.next().unwrap_or('\\'));
+            }
+            c => field.push(c),
+        }
+    }
+
+    let wildcard = raw_wildcard && field == "*";
+    fields.push((field, wildcard));
+
+    fields
+}
+
+// Get the home directory of the current user
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_service_file() {
+        let contents = "
+# Services
+[other]
+host=other.example.com
+
+[mydb]
+host = db.example.com
+port=5433
+  dbname=my db
+
+[after]
+user=nobody
+";
+
+        let params = parse_service_file(contents, "mydb").unwrap().unwrap();
+        assert_eq!(
+            params,
+            vec![
+                ("host".to_string(), "db.example.com".to_string()),
+                ("port".to_string(), "5433".to_string()),
+                ("dbname".to_string(), "my db".to_string()),
+            ]
+        );
+
+        assert!(parse_service_file(contents, "missing").unwrap().is_none());
+        assert!(parse_service_file("[mydb]\nhost", "mydb").is_err());
+    }
+
+    #[test]
+    fn test_find_password() {
+        let contents = "
+# hostname:port:database:username:password
+db.example.com:5432:mydb:alice:first
+*:*:*:bob:b\\:o\\\\b
+localhost:*:*:*:local
+\\*:5432:*:carol:literal star
+/tmp/custom:*:*:dave:custom socket
+";
+
+        assert_eq!(
+            find_password(contents, "db.example.com", "5432", "mydb", "alice").as_deref(),
+            Some("first")
+        );
+        assert_eq!(
+            find_password(contents, "db.example.com", "5433", "mydb", "alice"),
+            None
+        );
+        assert_eq!(
+            find_password(contents, "anywhere", "1", "any", "bob").as_deref(),
+            Some("b:o\\b")
+        );
+        assert_eq!(
+            find_password(contents, "localhost", "5432", "mydb", "alice").as_deref(),
+            Some("local")
+        );
+        assert_eq!(
+            find_password(contents, "", "5432", "mydb", "alice").as_deref(),
+            Some("local")
+        );
+        assert_eq!(
+            find_password(contents, "/tmp", "5432", "mydb", "alice").as_deref(),
+            Some("local")
+        );
+        assert_eq!(
+            find_password(contents, "/tmp/custom", "5432", "mydb", "alice"),
+            None
+        );
+        assert_eq!(
+            find_password(contents, "/tmp/custom", "5432", "mydb", "dave").as_deref(),
+            Some("custom socket")
+        );
+        assert_eq!(
+            find_password(contents, "db.example.com", "5432", "mydb", "carol"),
+            None
+        );
+        assert_eq!(
+            find_password(contents, "*", "5432", "mydb", "carol").as_deref(),
+            Some("literal star")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pgpass_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::sync::{Arc, Mutex};
+
+        let path = std::env::temp_dir().join(format!("lolrepl-pgpass-{}", std::process::id()));
+        fs::write(&path, "*:*:*:*:secret\n").unwrap();
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let handler: EventHandler = {
+            let warnings = warnings.clone();
+            Arc::new(move |event| warnings.lock().unwrap().push(event))
+        };
+
+        let ignored = pgpass_password(&path, "", 5432, "", "alice", Some(&handler)).unwrap();
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        let found = pgpass_password(&path, "", 5432, "", "alice", Some(&handler)).unwrap();
+
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(ignored, None);
+        assert!(matches!(
+            warnings.lock().unwrap().as_slice(),
+            [Event::Warning(_)]
+        ));
+        assert_eq!(found.as_deref(), Some("secret"));
+    }
+}
@@ -28,24 +28,59 @@ pub enum Error {
     InvalidMd5AuthRequest,
     /// General authentication failure with descriptive message.
     Authentication(String),
+    /// Invalid or malformed SCRAM message from server.
+    InvalidScramMessage(&'static str),
+    /// The SCRAM server signature did not match, the server could not prove it knows the password.
+    ScramServerSignatureMismatch,
+
+    /// Connection errors
+    /// The connection string could not be parsed.
+    InvalidConnectionString(String),
+    /// The connection service is not defined in any service file.
+    ServiceNotFound(String),
+    /// The server does not match the target session attributes, e.g. it is a standby
+    /// but a primary was asked for.
+    NoSuitableServer(String),
+    /// The cancel token does not know which server to connect to, because the connection
+    /// was made over a caller-supplied stream without a host in the options.
+    CancelHostUnknown,
+
+    /// Server errors
+    /// The server reported an error with an ErrorResponse, e.g. a failed replication
+    /// command, a rejected password or a canceled replication stream.
+    Db(Box<DbError>),
+    /// A slot command named a replication slot that does not exist (SQLSTATE 42704).
+    ReplicationSlotDoesNotExist(Box<DbError>),
+    /// A slot command named a replication slot that another process is using (SQLSTATE 55006).
+    ReplicationSlotInUse(Box<DbError>),
 
     /// Replication errors
-    /// A replication command sent to the server failed.
-    ReplicationCommandFailed(String),
     /// Failed to enter COPY mode needed for replication streaming.
     ReplicationCopyModeNotStarted,
     /// Server violated the replication protocol in some way.
     ReplicationProtocolViolation(String),
     /// Replication stream timed out waiting for data.
     ReplicationStreamTimedOut,
+    /// The server version does not support a requested replication feature, e.g.
+    /// failover slots before PostgreSQL 17.
+    UnsupportedServerVersion(String),
+    /// A replication command was rejected before it was sent, e.g. because of an invalid
+    /// slot name or an `ALTER_REPLICATION_SLOT` without options.
+    InvalidReplicationCommand(String),
+
+    /// TLS errors
+    /// The server does not accept TLS connections but the SSL mode requires TLS.
+    TlsNotSupported,
+    /// Error from the TLS backend, e.g. a failed handshake or certificate verification.
+    Tls(Box<dyn std::error::Error + Send + Sync>),
 
     /// Startup errors
-    /// Server startup process failed with error message.
-    ServerStartupFailure(String),
     /// Backend key data received during startup has invalid format.
     BackendKeyDataInvalid,
     /// Parameter status message received during startup has invalid format.
     ParameterStatusInvalid,
+    /// The server reported a setting the value parsers do not support, e.g. a non-ISO DateStyle.
+    UnsupportedServerSetting(String),
 
     /// Data parsing errors
     /// UTF-8 decoding error when parsing string data.
@@ -77,11 +112,29 @@ impl fmt::Display for Error {
             Error::InvalidAuthRequest => write!(f, "Invalid authentication request"),
             Error::InvalidMd5AuthRequest => write!(f, "Invalid MD5 auth request"),
             Error::Authentication(msg) => write!(f, "Authentication error: {}", msg),
+            Error::InvalidScramMessage(context) => {
+                write!(f, "Invalid SCRAM message: {}", context)
+            }
+            Error::ScramServerSignatureMismatch => {
+                write!(f, "SCRAM server signature does not match")
+            }
+            Error::InvalidConnectionString(msg) => write!(f, "Invalid connection string: {}", msg),
+            Error::NoSuitableServer(msg) => write!(f, "No suitable server: {}", msg),
+            Error::CancelHostUnknown => write!(
+                f,
+                "Server to send the cancel request to is unknown, use CancelToken::cancel_with"
+            ),
+            Error::ServiceNotFound(name) => {
+                write!(f, "Definition of service \"{}\" not found", name)
+            }
 
-            // Updated replication errors
-            Error::ReplicationCommandFailed(msg) => {
-                write!(f, "Replication command failed: {}", msg)
+            Error::Db(err) => write!(f, "Database error: {}", err),
+            Error::ReplicationSlotDoesNotExist(err) => {
+                write!(f, "Replication slot does not exist: {}", err)
             }
+            Error::ReplicationSlotInUse(err) => write!(f, "Replication slot is in use: {}", err),
+
+            // Updated replication errors
             Error::ReplicationCopyModeNotStarted => {
                 write!(f, "Failed to enter copy mode for replication")
             }
@@ -91,11 +144,22 @@ impl fmt::Display for Error {
             Error::ReplicationStreamTimedOut => {
                 write!(f, "Replication stream timed out waiting for data")
             }
+            Error::UnsupportedServerVersion(feature) => {
+                write!(f, "Not supported by the server version: {}", feature)
+            }
+            Error::InvalidReplicationCommand(msg) => {
+                write!(f, "Invalid replication command: {}", msg)
+            }
+
+            Error::TlsNotSupported => write!(f, "Server does not support TLS connections"),
+            Error::Tls(err) => write!(f, "TLS error: {}", err),
 
             // Updated startup errors
-            Error::ServerStartupFailure(msg) => write!(f, "Server startup failure: {}", msg),
             Error::BackendKeyDataInvalid => write!(f, "Invalid backend key data format"),
             Error::ParameterStatusInvalid => write!(f, "Invalid parameter status format"),
+            Error::UnsupportedServerSetting(setting) => {
+                write!(f, "Unsupported server setting: {}", setting)
+            }
 
             Error::Utf8(err) => write!(f, "UTF-8 error: {}", err),
             Error::HexDecode(err) => write!(f, "Hex decode error: {}", err),
@@ -111,6 +175,10 @@ impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::Io(err) => Some(err),
+            Error::Db(err)
+            | Error::ReplicationSlotDoesNotExist(err)
+            | Error::ReplicationSlotInUse(err) => Some(err.as_ref()),
+            Error::Tls(err) => Some(err.as_ref()),
             Error::Utf8(err) => Some(err),
             Error::HexDecode(err) => Some(err),
             Error::ParseInt(err) => Some(err),
@@ -121,6 +189,147 @@ impl std::error::Error for Error {
     }
 }
 
+impl Error {
+    /// Get the error reported by the server, if this is a server error.
+    pub fn as_db_error(&self) -> Option<&DbError> {
+        match self {
+            Error::Db(err)
+            | Error::ReplicationSlotDoesNotExist(err)
+            | Error::ReplicationSlotInUse(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Get the SQLSTATE code of the error reported by the server, e.g. `42704`.
+    ///
+    /// Returns `None` for errors that did not come from the server.
+    pub fn code(&self) -> Option<&str> {
+        self.as_db_error().map(|err| err.code.as_str())
+    }
+}
+
+/// An error or notice reported by the server, with all fields of the ErrorResponse.
+///
+/// See the PostgreSQL documentation on error and notice message fields for the
+/// meaning of each field. Only `severity`, `code` and `message` are always present.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DbError {
+    /// The severity, e.g. `ERROR`, `FATAL` or `WARNING`, never localized.
+    pub severity: String,
+    /// The SQLSTATE code, e.g. `42704` for an undefined object.
+    pub code: String,
+    /// The primary human-readable message.
+    pub message: String,
+    /// An optional secondary message with more detail.
+    pub detail: Option<String>,
+    /// An optional suggestion what to do about the problem.
+    pub hint: Option<String>,
+    /// The 1-based character position of the error in the query.
+    pub position: Option<u32>,
+    /// The position of the error in an internally generated query.
+    pub internal_position: Option<u32>,
+    /// The internally generated query that failed, e.g. in a PL/pgSQL function.
+    pub internal_query: Option<String>,
+    /// The context the error occurred in, e.g. a call stack of functions.
+    pub context: Option<String>,
+    /// The schema of the object associated with the error.
+    pub schema: Option<String>,
+    /// The table associated with the error.
+    pub table: Option<String>,
+    /// The column associated with the error.
+    pub column: Option<String>,
+    /// The data type associated with the error.
+    pub datatype: Option<String>,
+    /// The constraint associated with the error.
+    pub constraint: Option<String>,
+    /// The server source file that reported the error.
+    pub file: Option<String>,
+    /// The line in the server source file that reported the error.
+    pub line: Option<u32>,
+    /// The server source routine that reported the error.
+    pub routine: Option<String>,
+}
+
+impl DbError {
+    // Parse the fields of an ErrorResponse or NoticeResponse payload
+    pub(crate) fn parse(data: &[u8]) -> Result<DbError, Error> {
+        let mut err = DbError::default();
+        let mut localized_severity = None;
+        let mut rest = data;
+
+        loop {
+            let Some((&field_type, after_type)) = rest.split_first() else {
+                return Err(Error::UnexpectedEndOfData("error response"));
+            };
+
+            // A zero byte terminates the field list
+            if field_type == 0 {
+                break;
+            }
+
+            let end = after_type
+                .iter()
+                .position(|b| *b == 0)
+                .ok_or(Error::UnterminatedString)?;
+            let value = String::from_utf8_lossy(&after_type[..end]).into_owned();
+            rest = &after_type[end + 1..];
+
+            match field_type {
+                b'S' => localized_severity = Some(value),
+                b'V' => err.severity = value,
+                b'C' => err.code = value,
+                b'M' => err.message = value,
+                b'D' => err.detail = Some(value),
+                b'H' => err.hint = Some(value),
+                b'P' => err.position = value.parse().ok(),
+                b'p' => err.internal_position = value.parse().ok(),
+                b'q' => err.internal_query = Some(value),
+                b'W' => err.context = Some(value),
+                b's' => err.schema = Some(value),
+                b't' => err.table = Some(value),
+                b'c' => err.column = Some(value),
+                b'd' => err.datatype = Some(value),
+                b'n' => err.constraint = Some(value),
+                b'F' => err.file = Some(value),
+                b'L' => err.line = value.parse().ok(),
+                b'R' => err.routine = Some(value),
+                _ => {} // Unknown fields must be ignored
+            }
+        }
+
+        // Servers before 9.6 only send the possibly localized severity
+        if err.severity.is_empty() {
+            err.severity = localized_severity.unwrap_or_default();
+        }
+
+        Ok(err)
+    }
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+        if !self.code.is_empty() {
+            write!(f, " (SQLSTATE {})", self.code)?;
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, "\nDETAIL: {}", detail)?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\nHINT: {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DbError {}
+
+impl From<DbError> for Error {
+    fn from(err: DbError) -> Self {
+        Error::Db(Box::new(err))
+    }
+}
+
 // Implement From conversions for common error types
 impl From<io::Error> for Error {
     fn from(err: io::Error) -> Self {
@@ -163,3 +372,39 @@ impl From<FromUtf8Error> for Error {
         Error::Utf8(err.utf8_error())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_db_error() {
+        let data = b"SERROR\0VERROR\0C42704\0Mreplication slot \"x\" does not exist\0\
+            Hcreate it first\0P12\0Fslot.c\0L531\0RReplicationSlotAcquire\0Xunknown\0\0";
+
+        let err = DbError::parse(data).unwrap();
+
+        assert_eq!(err.severity, "ERROR");
+        assert_eq!(err.code, "42704");
+        assert_eq!(err.message, "replication slot \"x\" does not exist");
+        assert_eq!(err.hint.as_deref(), Some("create it first"));
+        assert_eq!(err.position, Some(12));
+        assert_eq!(err.file.as_deref(), Some("slot.c"));
+        assert_eq!(err.line, Some(531));
+        assert_eq!(err.routine.as_deref(), Some("ReplicationSlotAcquire"));
+        assert_eq!(err.detail, None);
+
+        let err = Error::from(err);
+        assert_eq!(err.code(), Some("42704"));
+    }
+
+    #[test]
+    fn test_parse_db_error_localized_severity() {
+        let err = DbError::parse(b"SFEHLER\0C28P01\0MPasswort falsch\0\0").unwrap();
+        assert_eq!(err.severity, "FEHLER");
+        assert_eq!(err.code, "28P01");
+
+        assert!(DbError::parse(b"SERROR\0Munterminated").is_err());
+        assert!(DbError::parse(b"SERROR\0").is_err());
+    }
+}
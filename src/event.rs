@@ -0,0 +1,58 @@
+use crate::error::DbError;
+use std::sync::Arc;
+
+/// An event that does not affect the result of an operation but may be of interest.
+///
+/// Events are passed to the handler set with `ConnectOptions::event_handler`,
+/// `Connection::set_event_handler` or `Subscriber::set_event_handler`. Without a
+/// handler they are dropped, or logged through the `log` facade when the `log`
+/// feature is enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A NoticeResponse from the server, e.g. a `WARNING` or `NOTICE` raised by a command.
+    Notice(Box<DbError>),
+    /// The server reported a new value for a parameter, e.g. `TimeZone` after a reload.
+    ParameterStatus {
+        /// The parameter name.
+        name: String,
+        /// The new value.
+        value: String,
+    },
+    /// The server sent a protocol message of a type that is not handled and was skipped.
+    UnknownMessage(u8),
+    /// Something unexpected happened on the client side, e.g. a password file with
+    /// unsafe permissions was ignored or a value could not be decoded.
+    Warning(String),
+}
+
+// A shared event handler, cloned along with the options it was set on
+pub(crate) type EventHandler = Arc<dyn Fn(Event) + Send + Sync>;
+
+// Pass an event to the handler, or fall back to the log facade if enabled
+pub(crate) fn emit(handler: Option<&EventHandler>, event: Event) {
+    match handler {
+        Some(handler) => handler(event),
+        None => log_event(event),
+    }
+}
+
+#[cfg(feature = "log")]
+fn log_event(event: Event) {
+    match event {
+        Event::Notice(notice) => match notice.severity.as_str() {
+            "WARNING" => log::warn!("{}", notice),
+            "DEBUG" => log::debug!("{}", notice),
+            _ => log::info!("{}", notice),
+        },
+        Event::ParameterStatus { name, value } => {
+            log::debug!("Server parameter {} changed to {}", name, value)
+        }
+        Event::UnknownMessage(message_type) => {
+            log::debug!("Skipped unknown message type: {}", message_type as char)
+        }
+        Event::Warning(warning) => log::warn!("{}", warning),
+    }
+}
+
+#[cfg(not(feature = "log"))]
+fn log_event(_event: Event) {}
@@ -9,9 +9,23 @@
 //! # Features
 //!
 //! - Establishes replication connections to PostgreSQL
-//! - Handles authentication (cleartext and MD5)
+//! - Handles authentication (cleartext, MD5 and SCRAM-SHA-256)
+//! - Speaks protocol 3.0, or 3.2 when requested, negotiating down for older servers
+//! - Encrypts connections with TLS, with a `native-tls` backend behind the `native-tls` feature
+//! - Connects from `postgresql://` URIs or `key=value` connection strings, over TCP or Unix sockets
+//! - Fails over between several hosts, selecting a primary or standby with `target_session_attrs`
+//! - Optionally resolves connection settings from `PG*` environment variables, `.pgpass` and `pg_service.conf`
+//! - Identifies the server with `IDENTIFY_SYSTEM`, and creates, inspects, alters and drops replication slots
+//! - Runs SQL such as `SHOW wal_level` on the replication connection with `simple_query`
+//! - Cancels or terminates a running replication session from another thread
+//! - Reports server notices and other events to a handler, or the `log` facade with the `log` feature
 //! - Parses logical replication WAL messages
-//! - Supports various PostgreSQL data types
+//! - Represents WAL locations as `Lsn` values in PostgreSQL's `X/X` form
+//! - Reports only explicitly acknowledged locations as flushed, so the server keeps WAL that was not processed
+//! - Sends status updates often enough for `wal_sender_timeout` and detects a server that went silent
+//! - Reports the origin of changes replayed from other nodes, and can stream only local changes
+//! - Streams messages written with `pg_logical_emit_message`, e.g. for outbox events
+//! - Supports various PostgreSQL data types, and decodes enum, domain and extension types described by the server
 //! - Provides error handling for replication operations
 //!
 //! # Prerequisites
@@ -70,8 +84,11 @@
 //!     // Stream replication messages
 //!     loop {
 //!         match subscriber.next()? {
-//!             Message::Begin(lsn) => {
-//!                 println!("Transaction started at LSN: {}", lsn);
+//!             Message::Begin { final_lsn, xid, .. } => {
+//!                 println!("Transaction {} started, commits at LSN: {}", xid, final_lsn);
+//!             }
+//!             Message::Origin { name, .. } => {
+//!                 println!("Transaction replayed from origin {}", name);
 //!             }
 //!             Message::Relation { id, namespace, name, .. } => {
 //!                 println!("Relation definition: {}.{} (ID: {})", namespace, name, id);
@@ -109,8 +126,23 @@
 //!                     }
 //!                 }
 //!             }
-//!             Message::Commit(lsn) => {
-//!                 println!("Transaction committed at LSN: {}", lsn);
+//!             Message::Type { id, namespace, name } => {
+//!                 println!("Type definition: {}.{} (ID: {})", namespace, name, id);
+//!             }
+//!             Message::Truncate { relation_ids, .. } => {
+//!                 for relation_id in relation_ids {
+//!                     if let Some(relation) = subscriber.relation_info(relation_id) {
+//!                         println!("TRUNCATE {}.{}", relation.namespace, relation.name);
+//!                     }
+//!                 }
+//!             }
+//!             Message::Commit { end_lsn, commit_time, .. } => {
+//!                 println!("Transaction committed at {}", commit_time);
+//!                 // Let the server recycle WAL once the transaction is processed
+//!                 subscriber.ack(end_lsn);
+//!             }
+//!             Message::LogicalMessage { prefix, content, .. } => {
+//!                 println!("Message {}: {} bytes", prefix, content.len());
 //!             }
 //!             Message::Unknown(msg_type) => {
 //!                 println!("Unknown message type: {}", msg_type);
@@ -120,6 +152,14 @@
 //! }
 //! ```
 //!
+//! ## Acknowledging Changes
+//!
+//! The slot only advances past locations acknowledged with `Subscriber::ack`, as in the
+//! `Commit` arm above. A consumer that never calls it keeps the server from recycling WAL,
+//! which then grows until the disk fills up. Earlier versions acknowledged every location
+//! as soon as it was received; `SubscriberOptions::auto_ack` restores that behavior for
+//! consumers that can afford to miss changes after a crash.
+//!
 //! ## Processing Specific Value Types
 //!
 //! ```rust,no_run
@@ -172,6 +212,10 @@
 //!                 // Process message
 //!                 println!("Received: {:?}", message);
 //!             }
+//!             Err(Error::Db(ref e)) if e.code == "57014" => {
+//!                 // The replication command was canceled
+//!                 return Ok(());
+//!             }
 //!             Err(Error::Io(ref e)) if e.kind() == std::io::ErrorKind::TimedOut => {
 //!                 // Timeout is normal, continue
 //!                 continue;
@@ -185,12 +229,42 @@
 //! }
 //! ```
 
+mod cancel;
+mod config;
 mod conn;
+mod env;
 mod error;
+mod event;
+mod lsn;
+#[cfg(feature = "native-tls")]
+mod native_tls;
+mod query;
+mod replication;
+mod scram;
+mod socket;
 mod sub;
+mod tls;
 mod value;
 
+pub use cancel::CancelToken;
+pub use config::{ConnectOptions, ProtocolVersion, TargetSessionAttrs};
 pub use conn::Connection;
-pub use error::Error;
-pub use sub::{Column, Message, RelationInfo, Subscriber};
+pub use error::{DbError, Error};
+pub use event::Event;
+pub use lsn::Lsn;
+#[cfg(feature = "native-tls")]
+pub use native_tls::{NativeTlsConnector, NativeTlsStream};
+pub use query::{QueryColumn, QueryResult};
+pub use replication::{
+    AlterSlotOptions, CreateSlotOptions, ReplicationSlot, ReplicationSlotInfo, SlotType,
+    SnapshotAction, SystemIdentity,
+};
+pub use socket::{SetReadTimeout, Socket};
+pub use sub::{
+    Column, Message, OriginFilter, RelationInfo, Subscriber, SubscriberOptions, TypeInfo,
+};
+pub use tls::{
+    ChannelBinding, ChannelBindingMode, MaybeTlsStream, NoTls, NoTlsStream, SslMode, TlsConnect,
+    TlsStream,
+};
 pub use value::Value;
@@ -0,0 +1,207 @@
+use crate::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Limits of wal_segment_size, which must also be a power of two
+const MIN_SEGMENT_SIZE: u64 = 1024 * 1024;
+const MAX_SEGMENT_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// A log sequence number, a byte position in the write-ahead log.
+///
+/// Displayed and parsed in the `X/X` form PostgreSQL uses, e.g. `0/16B3748`, so values can
+/// be compared with the `pg_lsn` columns of `pg_replication_slots`:
+///
+/// ```rust
+/// use lolrepl::Lsn;
+///
+/// # fn main() -> Result<(), lolrepl::Error> {
+/// let confirmed: Lsn = "0/16B3748".parse()?;
+/// let received = Lsn::from(0x16B4000);
+///
+/// assert_eq!(received.to_string(), "0/16B4000");
+/// assert_eq!(received.checked_sub(confirmed), Some(0x8B8));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lsn(pub u64);
+
+impl Lsn {
+    /// The invalid location `0/0`, e.g. to start replication from the slot's position.
+    pub const INVALID: Lsn = Lsn(0);
+
+    /// Get the number of bytes between an earlier location and this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `earlier` - The location to measure from, e.g. the last acknowledged location
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing the distance in bytes, or `None` if `earlier` is
+    /// after this location.
+    pub fn checked_sub(self, earlier: Lsn) -> Option<u64> {
+        self.0.checked_sub(earlier.0)
+    }
+
+    /// Get the location a number of bytes after this one.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing the new location, or `None` on overflow.
+    pub fn checked_add(self, bytes: u64) -> Option<Lsn> {
+        self.0.checked_add(bytes).map(Lsn)
+    }
+
+    /// Get the number of the WAL segment that contains this location.
+    ///
+    /// # Arguments
+    ///
+    /// * `segment_size` - The server's `wal_segment_size` in bytes, 16 MB by default
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing the segment number, or `None` if `segment_size` is
+    /// not a power of two from 1 MB to 1 GB, the sizes `initdb` accepts.
+    pub fn segment_number(self, segment_size: u64) -> Option<u64> {
+        if !is_valid_segment_size(segment_size) {
+            return None;
+        }
+
+        Some(self.0 / segment_size)
+    }
+
+    /// Get the name of the WAL segment file that contains this location.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeline` - The timeline of the location, e.g. from `Connection::identify_system`
+    /// * `segment_size` - The server's `wal_segment_size` in bytes, 16 MB by default
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing the 24 hexadecimal digit file name, e.g.
+    /// `000000010000000000000001`, or `None` if `segment_size` is not a power of two from
+    /// 1 MB to 1 GB.
+    pub fn segment_file_name(self, timeline: u32, segment_size: u64) -> Option<String> {
+        let segment = self.segment_number(segment_size)?;
+        let segments_per_id = 0x1_0000_0000 / segment_size;
+
+        Some(format!(
+            "{:08X}{:08X}{:08X}",
+            timeline,
+            segment / segments_per_id,
+            segment % segments_per_id
+        ))
+    }
+}
+
+// Check that a segment size is one the server can be configured with
+fn is_valid_segment_size(segment_size: u64) -> bool {
+    segment_size.is_power_of_two() && (MIN_SEGMENT_SIZE..=MAX_SEGMENT_SIZE).contains(&segment_size)
+}
+
+impl From<u64> for Lsn {
+    fn from(lsn: u64) -> Self {
+        Lsn(lsn)
+    }
+}
+
+impl From<Lsn> for u64 {
+    fn from(lsn: Lsn) -> Self {
+        lsn.0
+    }
+}
+
+impl fmt::Display for Lsn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:X}/{:X}", self.0 >> 32, self.0 as u32)
+    }
+}
+
+impl FromStr for Lsn {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (high, low) = s
+            .split_once('/')
+            .ok_or_else(|| Error::ParseValue(format!("Invalid LSN: {}", s)))?;
+
+        let high = u32::from_str_radix(high, 16)?;
+        let low = u32::from_str_radix(low, 16)?;
+
+        Ok(Lsn((high as u64) << 32 | low as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEGMENT_SIZE: u64 = 16 * 1024 * 1024;
+
+    #[test]
+    fn test_parse_lsn() {
+        assert_eq!("0/0".parse::<Lsn>().unwrap(), Lsn::INVALID);
+        assert_eq!("0/16B3748".parse::<Lsn>().unwrap(), Lsn(0x16B3748));
+        assert_eq!("1/ff".parse::<Lsn>().unwrap(), Lsn(0x1_0000_00FF));
+        assert!("16B3748".parse::<Lsn>().is_err());
+        assert!("0/XYZ".parse::<Lsn>().is_err());
+        assert!("1/123456789".parse::<Lsn>().is_err());
+    }
+
+    #[test]
+    fn test_display_lsn() {
+        assert_eq!(Lsn(0).to_string(), "0/0");
+        assert_eq!(Lsn(0x16B3748).to_string(), "0/16B3748");
+        assert_eq!(Lsn(0x1_0000_00FF).to_string(), "1/FF");
+
+        let lsn = Lsn(0xABCD_1234_5678);
+        assert_eq!(lsn.to_string().parse::<Lsn>().unwrap(), lsn);
+    }
+
+    #[test]
+    fn test_lsn_arithmetic() {
+        let earlier = Lsn(0xFFFF_FF00);
+        let later = Lsn(0x1_0000_0100);
+
+        assert!(earlier < later);
+        assert_eq!(later.checked_sub(earlier), Some(0x200));
+        assert_eq!(earlier.checked_sub(later), None);
+        assert_eq!(earlier.checked_add(0x200), Some(later));
+        assert_eq!(Lsn(u64::MAX).checked_add(1), None);
+    }
+
+    #[test]
+    fn test_segment_file_name() {
+        assert_eq!(
+            Lsn(0x16B3748).segment_file_name(1, SEGMENT_SIZE).unwrap(),
+            "000000010000000000000001"
+        );
+        assert_eq!(
+            "2A/FF000001"
+                .parse::<Lsn>()
+                .unwrap()
+                .segment_file_name(3, SEGMENT_SIZE)
+                .unwrap(),
+            "000000030000002A000000FF"
+        );
+        assert_eq!(
+            Lsn(0x1_4000_0000)
+                .segment_file_name(1, 1024 * 1024 * 1024)
+                .unwrap(),
+            "000000010000000100000001"
+        );
+        assert_eq!(Lsn(0x3000000).segment_number(SEGMENT_SIZE), Some(3));
+    }
+
+    #[test]
+    fn test_segment_size_limits() {
+        assert_eq!(Lsn(0x300000).segment_number(1024 * 1024), Some(3));
+
+        for segment_size in [0, 512 * 1024, 24 * 1024 * 1024, 2 * 1024 * 1024 * 1024] {
+            assert!(Lsn(0).segment_number(segment_size).is_none());
+            assert!(Lsn(0).segment_file_name(1, segment_size).is_none());
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::Error;
+use crate::socket::SetReadTimeout;
+use crate::tls::{ChannelBinding, SslMode, TlsConnect, TlsStream};
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+/// A TLS backend based on the `native-tls` crate.
+///
+/// Certificates are verified according to the SSL mode: `Prefer` and `Require` accept
+/// any certificate, `VerifyCa` checks the chain and `VerifyFull` also checks the host name.
+///
+/// ```rust,no_run
+/// use std::net::TcpStream;
+/// use lolrepl::{ConnectOptions, Connection, NativeTlsConnector, SslMode};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let root_certificate = std::fs::read("root.crt")?;
+/// let tls = NativeTlsConnector::new()
+///     .add_root_certificate(native_tls::Certificate::from_pem(&root_certificate)?);
+///
+/// let options = ConnectOptions::new()
+///     .host("db.example.com")
+///     .user("replication_user")
+///     .password("password")
+///     .database("mydb")
+///     .ssl_mode(SslMode::VerifyFull);
+///
+/// let stream = TcpStream::connect("db.example.com:5432")?;
+/// let connection = Connection::with_tls(stream, &options, tls)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct NativeTlsConnector {
+    root_certificates: Vec<::native_tls::Certificate>,
+}
+
+impl NativeTlsConnector {
+    /// Create a new backend that trusts the system's root certificates.
+    pub fn new() -> Self {
+        NativeTlsConnector::default()
+    }
+
+    /// Add a certificate to trust in addition to the system's root certificates.
+    pub fn add_root_certificate(mut self, certificate: ::native_tls::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+}
+
+impl<S: Read + Write> TlsConnect<S> for NativeTlsConnector {
+    type Stream = NativeTlsStream<S>;
+
+    fn connect(self, stream: S, domain: &str, mode: SslMode) -> Result<Self::Stream, Error> {
+        let mut builder = ::native_tls::TlsConnector::builder();
+
+        for certificate in self.root_certificates {
+            builder.add_root_certificate(certificate);
+        }
+
+        match mode {
+            SslMode::VerifyFull => {}
+            SslMode::VerifyCa => {
+                builder.danger_accept_invalid_hostnames(true);
+            }
+            _ => {
+                builder.danger_accept_invalid_certs(true);
+                builder.danger_accept_invalid_hostnames(true);
+            }
+        }
+
+        // Without a host name there is nothing to send as SNI
+        if domain.is_empty() {
+            builder.use_sni(false);
+        }
+
+        let connector = builder.build().map_err(|e| Error::Tls(Box::new(e)))?;
+
+        match connector.connect(domain, stream) {
+            Ok(stream) => Ok(NativeTlsStream(stream)),
+            Err(::native_tls::HandshakeError::Failure(e)) => Err(Error::Tls(Box::new(e))),
+            Err(::native_tls::HandshakeError::WouldBlock(_)) => Err(Error::Io(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "TLS handshake would block",
+            ))),
+        }
+    }
+}
+
+/// A stream encrypted with TLS by `NativeTlsConnector`.
+pub struct NativeTlsStream<S>(::native_tls::TlsStream<S>);
+
+impl<S: Read + Write> Read for NativeTlsStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<S: Read + Write> Write for NativeTlsStream<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+// The timeout applies to the socket underneath the TLS session
+impl<S: SetReadTimeout> SetReadTimeout for NativeTlsStream<S> {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        self.0.get_ref().read_timeout()
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.0.get_ref().set_read_timeout(timeout)
+    }
+}
+
+impl<S: Read + Write> TlsStream for NativeTlsStream<S> {
+    fn channel_binding(&self) -> ChannelBinding {
+        match self.0.tls_server_end_point() {
+            Ok(Some(hash)) => ChannelBinding::tls_server_end_point(hash),
+            _ => ChannelBinding::none(),
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use crate::Error;
+use crate::conn::Connection;
+use crate::error::DbError;
+use crate::event::Event;
+use crate::value::{Value, has_text_parser, parse_text_value};
+use std::io::{Read, Write};
+
+/// A column of a query result, as described by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryColumn {
+    /// The name of the column.
+    pub name: String,
+    /// The PostgreSQL type OID of the column.
+    pub type_id: u32,
+    /// The type modifier of the column, e.g. the length of a `varchar(n)`.
+    pub type_modifier: i32,
+}
+
+/// The result of one statement run with `Connection::simple_query`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryResult {
+    /// The columns of the rows, empty for statements that return no rows.
+    pub columns: Vec<QueryColumn>,
+    /// The rows returned by the statement, with `Value::Null` for NULL and
+    /// `Value::Unknown` holding the text of types without a parser.
+    pub rows: Vec<Vec<Value>>,
+    /// The command tag, e.g. `SELECT 2`, `INSERT 0 1` or `SHOW`.
+    pub command_tag: String,
+}
+
+impl QueryResult {
+    /// Get the index of a column by its name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the column
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing the index of the first column with the name.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column.name == name)
+    }
+
+    /// Get the number of rows the statement returned or changed, from the command tag.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing the row count, or `None` for commands whose tag
+    /// has no count, e.g. `SHOW` or `CREATE TABLE`.
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.command_tag.rsplit(' ').next()?.parse().ok()
+    }
+}
+
+// The result of one statement with values in text format
+#[derive(Default)]
+struct TextResult {
+    columns: Vec<QueryColumn>,
+    rows: Vec<Vec<Option<String>>>,
+    command_tag: String,
+}
+
+impl TextResult {
+    // Parse the values with the types from the row description
+    fn into_query_result(self) -> Result<QueryResult, Error> {
+        let rows = self
+            .rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .zip(&self.columns)
+                    .map(|(value, column)| text_to_value(value, column.type_id))
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(QueryResult {
+            columns: self.columns,
+            rows,
+            command_tag: self.command_tag,
+        })
+    }
+}
+
+impl<T: Read + Write> Connection<T> {
+    /// Run SQL or a replication command with the simple query protocol.
+    ///
+    /// A replication connection to a database accepts ordinary SQL as long as replication
+    /// has not been started, e.g. `SHOW wal_level` or queries of `pg_replication_slots`.
+    /// If the server reports an error, the rest of the response is consumed before
+    /// returning it, so the connection stays usable.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - One or more statements separated by semicolons
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing a `QueryResult` for each statement on success, or an
+    /// `Error` on failure.
+    pub fn simple_query(&mut self, query: &str) -> Result<Vec<QueryResult>, Error> {
+        self.text_query(query)?
+            .into_iter()
+            .map(TextResult::into_query_result)
+            .collect()
+    }
+
+    /// Run a query with the simple query protocol and collect the rows of its results.
+    ///
+    /// Values are returned in text format, `None` for NULL.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The SQL or replication command to run
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the rows on success, or an `Error` on failure.
+    pub(crate) fn query(&mut self, query: &str) -> Result<Vec<Vec<Option<String>>>, Error> {
+        Ok(self
+            .text_query(query)?
+            .into_iter()
+            .flat_map(|result| result.rows)
+            .collect())
+    }
+
+    // Run a query and collect the results of its statements in text format
+    fn text_query(&mut self, query: &str) -> Result<Vec<TextResult>, Error> {
+        let mut query_data = Vec::with_capacity(query.len() + 1);
+        query_data.extend_from_slice(query.as_bytes());
+        query_data.push(0); // null terminator
+        self.write_message(b'Q', &query_data, false)?;
+
+        let mut results = Vec::new();
+        let mut current: Option<TextResult> = None;
+        let mut error = None;
+
+        loop {
+            let message = self.read_message(false)?;
+
+            match message.message_type {
+                b'T' => {
+                    // RowDescription, starts the result of a statement that returns rows
+                    current = Some(TextResult {
+                        columns: parse_row_description(&message.data)?,
+                        ..TextResult::default()
+                    });
+                }
+                b'D' => {
+                    // DataRow
+                    current
+                        .get_or_insert_with(TextResult::default)
+                        .rows
+                        .push(parse_data_row(&message.data)?);
+                }
+                b'C' => {
+                    // CommandComplete, ends the result of a statement
+                    let mut result = current.take().unwrap_or_default();
+                    result.command_tag = read_cstring(&mut message.data.as_slice())?.to_string();
+                    results.push(result);
+                }
+                b'I' => {
+                    // EmptyQueryResponse
+                }
+                b'E' => {
+                    // ErrorResponse, the server still sends ReadyForQuery afterwards
+                    error = Some(DbError::parse(&message.data)?.into());
+                }
+                b'N' => {
+                    // NoticeResponse
+                    let notice = DbError::parse(&message.data)?;
+                    self.emit(Event::Notice(Box::new(notice)));
+                }
+                b'S' => {
+                    // ParameterStatus, e.g. after a SET
+                    self.handle_parameter_status(&message.data)?;
+                }
+                b'Z' => {
+                    // ReadyForQuery
+                    break;
+                }
+                _ => {
+                    return Err(Error::ReplicationProtocolViolation(format!(
+                        "Unexpected message type in query response: {}",
+                        message.message_type as char
+                    )));
+                }
+            }
+        }
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(results),
+        }
+    }
+}
+
+// Parse a text value, keeping the text of types without a parser as Value::Unknown
+fn text_to_value(value: Option<String>, type_id: u32) -> Result<Value, Error> {
+    match value {
+        None => Ok(Value::Null),
+        Some(text) if has_text_parser(type_id) => parse_text_value(&text, type_id),
+        Some(text) => Ok(Value::Unknown(text.into_bytes(), type_id)),
+    }
+}
+
+// Read the columns of a RowDescription payload
+fn parse_row_description(data: &[u8]) -> Result<Vec<QueryColumn>, Error> {
+    if data.len() < 2 {
+        return Err(Error::UnexpectedEndOfData("row description"));
+    }
+
+    let column_count = u16::from_be_bytes([data[0], data[1]]) as usize;
+    let mut columns = Vec::with_capacity(column_count);
+    let mut rest = &data[2..];
+
+    for _ in 0..column_count {
+        let name = read_cstring(&mut rest)?.to_string();
+
+        // Table OID (4), column number (2), type OID (4), type size (2), type modifier (4)
+        // and format code (2)
+        if rest.len() < 18 {
+            return Err(Error::UnexpectedEndOfData("row description field"));
+        }
+
+        let type_id = u32::from_be_bytes([rest[6], rest[7], rest[8], rest[9]]);
+        let type_modifier = i32::from_be_bytes([rest[12], rest[13], rest[14], rest[15]]);
+        rest = &rest[18..];
+
+        columns.push(QueryColumn {
+            name,
+            type_id,
+            type_modifier,
+        });
+    }
+
+    Ok(columns)
+}
+
+// Split a DataRow payload into its text values
+fn parse_data_row(data: &[u8]) -> Result<Vec<Option<String>>, Error> {
+    if data.len() < 2 {
+        return Err(Error::UnexpectedEndOfData("data row"));
+    }
+
+    let column_count = u16::from_be_bytes([data[0], data[1]]) as usize;
+    let mut values = Vec::with_capacity(column_count);
+    let mut rest = &data[2..];
+
+    for _ in 0..column_count {
+        if rest.len() < 4 {
+            return Err(Error::UnexpectedEndOfData("data row value length"));
+        }
+
+        let length = i32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]);
+        rest = &rest[4..];
+
+        if length < 0 {
+            values.push(None);
+            continue;
+        }
+
+        let length = length as usize;
+        if rest.len() < length {
+            return Err(Error::UnexpectedEndOfData("data row value"));
+        }
+
+        values.push(Some(std::str::from_utf8(&rest[..length])?.to_string()));
+        rest = &rest[length..];
+    }
+
+    Ok(values)
+}
+
+// Read a null-terminated string and advance past it
+fn read_cstring<'a>(data: &mut &'a [u8]) -> Result<&'a str, Error> {
+    let end = data
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or(Error::UnterminatedString)?;
+    let s = std::str::from_utf8(&data[..end])?;
+    *data = &data[end + 1..];
+    Ok(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{PG_TYPE_INT4, PG_TYPE_TEXT};
+
+    // Build a RowDescription field
+    fn field(name: &str, type_id: u32, type_modifier: i32) -> Vec<u8> {
+        let mut data = name.as_bytes().to_vec();
+        data.push(0);
+        data.extend_from_slice(&0u32.to_be_bytes()); // table OID
+        data.extend_from_slice(&0i16.to_be_bytes()); // column number
+        data.extend_from_slice(&type_id.to_be_bytes());
+        data.extend_from_slice(&(-1i16).to_be_bytes()); // type size
+        data.extend_from_slice(&type_modifier.to_be_bytes());
+        data.extend_from_slice(&0i16.to_be_bytes()); // text format
+        data
+    }
+
+    #[test]
+    fn test_parse_row_description() {
+        let mut data = 2u16.to_be_bytes().to_vec();
+        data.extend(field("id", PG_TYPE_INT4, -1));
+        data.extend(field("slot_name", 19, -1));
+
+        assert_eq!(
+            parse_row_description(&data).unwrap(),
+            vec![
+                QueryColumn {
+                    name: "id".to_string(),
+                    type_id: PG_TYPE_INT4,
+                    type_modifier: -1,
+                },
+                QueryColumn {
+                    name: "slot_name".to_string(),
+                    type_id: 19,
+                    type_modifier: -1,
+                },
+            ]
+        );
+
+        assert!(parse_row_description(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_into_query_result() {
+        let column = |name: &str, type_id| QueryColumn {
+            name: name.to_string(),
+            type_id,
+            type_modifier: -1,
+        };
+
+        let result = TextResult {
+            columns: vec![
+                column("id", PG_TYPE_INT4),
+                column("name", PG_TYPE_TEXT),
+                column("xmin", 28),
+            ],
+            rows: vec![vec![Some("1".to_string()), None, Some("742".to_string())]],
+            command_tag: "SELECT 1".to_string(),
+        }
+        .into_query_result()
+        .unwrap();
+
+        assert_eq!(
+            result.rows,
+            vec![vec![
+                Value::Integer(1),
+                Value::Null,
+                Value::Unknown(b"742".to_vec(), 28),
+            ]]
+        );
+        assert_eq!(result.column_index("name"), Some(1));
+        assert_eq!(result.column_index("missing"), None);
+        assert_eq!(result.rows_affected(), Some(1));
+    }
+
+    #[test]
+    fn test_rows_affected() {
+        let result = |command_tag: &str| QueryResult {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            command_tag: command_tag.to_string(),
+        };
+
+        assert_eq!(result("INSERT 0 3").rows_affected(), Some(3));
+        assert_eq!(result("UPDATE 12").rows_affected(), Some(12));
+        assert_eq!(result("SHOW").rows_affected(), None);
+        assert_eq!(result("CREATE TABLE").rows_affected(), None);
+    }
+}
@@ -0,0 +1,711 @@
+use crate::Error;
+use crate::conn::Connection;
+use crate::lsn::Lsn;
+use std::io::{Read, Write};
+
+// Longest name the server accepts for a slot, NAMEDATALEN - 1
+const MAX_NAME_LENGTH: usize = 63;
+
+/// The identity of a server and its current WAL position, as reported by `IDENTIFY_SYSTEM`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemIdentity {
+    /// The unique identifier of the cluster, assigned by `initdb`.
+    ///
+    /// A cluster restored from a logical dump or re-initialized gets a new identifier,
+    /// so a change means slots and positions from before no longer apply.
+    pub system_id: u64,
+    /// The current timeline ID.
+    pub timeline: u32,
+    /// The current WAL flush location.
+    pub xlogpos: Lsn,
+    /// The database connected to, or `None` for a physical replication connection.
+    pub dbname: Option<String>,
+}
+
+/// What to do with the snapshot of the slot's consistent point when creating a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotAction {
+    /// Export the snapshot so other sessions can import it with `SET TRANSACTION SNAPSHOT`,
+    /// e.g. to copy the initial table contents. It stays valid until the next command on
+    /// the connection.
+    Export,
+    /// Use the snapshot for the current transaction of the connection, which must be a
+    /// `REPEATABLE READ` transaction that has not run a query yet.
+    Use,
+    /// Do not make the snapshot available.
+    Nothing,
+}
+
+impl SnapshotAction {
+    // The value of the SNAPSHOT option
+    fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotAction::Export => "export",
+            SnapshotAction::Use => "use",
+            SnapshotAction::Nothing => "nothing",
+        }
+    }
+
+    // The equivalent keyword before PostgreSQL 15
+    fn legacy_keyword(&self) -> &'static str {
+        match self {
+            SnapshotAction::Export => "EXPORT_SNAPSHOT",
+            SnapshotAction::Use => "USE_SNAPSHOT",
+            SnapshotAction::Nothing => "NOEXPORT_SNAPSHOT",
+        }
+    }
+}
+
+/// Options for creating a logical replication slot with `Connection::create_replication_slot`.
+///
+/// ```rust
+/// use lolrepl::{CreateSlotOptions, SnapshotAction};
+///
+/// let options = CreateSlotOptions::new()
+///     .temporary(true)
+///     .snapshot(SnapshotAction::Nothing);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateSlotOptions {
+    output_plugin: String,
+    temporary: bool,
+    two_phase: bool,
+    failover: bool,
+    snapshot: Option<SnapshotAction>,
+}
+
+impl Default for CreateSlotOptions {
+    fn default() -> Self {
+        CreateSlotOptions {
+            output_plugin: "pgoutput".to_string(),
+            temporary: false,
+            two_phase: false,
+            failover: false,
+            snapshot: None,
+        }
+    }
+}
+
+impl CreateSlotOptions {
+    /// Create options for a permanent `pgoutput` slot with the server's defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the output plugin that decodes changes for the slot, `pgoutput` by default.
+    pub fn output_plugin(mut self, output_plugin: &str) -> Self {
+        self.output_plugin = output_plugin.to_string();
+        self
+    }
+
+    /// Create a temporary slot, dropped when the connection closes or on error.
+    pub fn temporary(mut self, temporary: bool) -> Self {
+        self.temporary = temporary;
+        self
+    }
+
+    /// Enable decoding of prepared transactions, requires PostgreSQL 14.
+    pub fn two_phase(mut self, two_phase: bool) -> Self {
+        self.two_phase = two_phase;
+        self
+    }
+
+    /// Synchronize the slot to standbys so it survives a failover, requires PostgreSQL 17.
+    pub fn failover(mut self, failover: bool) -> Self {
+        self.failover = failover;
+        self
+    }
+
+    /// Set what to do with the snapshot, the server exports it if not set.
+    pub fn snapshot(mut self, snapshot: SnapshotAction) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+}
+
+/// A replication slot created with `Connection::create_replication_slot`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicationSlot {
+    /// The name of the slot.
+    pub slot_name: String,
+    /// The location from which the slot decodes changes; streaming from the slot starts
+    /// with the first transaction committed after it.
+    pub consistent_point: Lsn,
+    /// The name of the exported snapshot, if the snapshot was exported.
+    pub snapshot_name: Option<String>,
+    /// The output plugin of the slot.
+    pub output_plugin: Option<String>,
+}
+
+/// Options for changing a replication slot with `Connection::alter_replication_slot`.
+///
+/// Options that are not set keep their current value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AlterSlotOptions {
+    failover: Option<bool>,
+    two_phase: Option<bool>,
+}
+
+impl AlterSlotOptions {
+    /// Create options that change nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether the slot is synchronized to standbys, requires PostgreSQL 17.
+    pub fn failover(mut self, failover: bool) -> Self {
+        self.failover = Some(failover);
+        self
+    }
+
+    /// Set whether prepared transactions are decoded, requires PostgreSQL 18.
+    pub fn two_phase(mut self, two_phase: bool) -> Self {
+        self.two_phase = Some(two_phase);
+        self
+    }
+}
+
+/// The kind of a replication slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotType {
+    /// A physical slot, retaining WAL for a streaming standby or backup.
+    Physical,
+    /// A logical slot, decoding changes with an output plugin.
+    Logical,
+}
+
+/// Information about a replication slot, as reported by `READ_REPLICATION_SLOT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicationSlotInfo {
+    /// The kind of the slot.
+    pub slot_type: SlotType,
+    /// The oldest location the slot still needs, if it has reserved WAL.
+    pub restart_lsn: Option<Lsn>,
+    /// The timeline of the restart location, if it has reserved WAL.
+    pub restart_tli: Option<u32>,
+}
+
+impl<T: Read + Write> Connection<T> {
+    /// Identify the server with the `IDENTIFY_SYSTEM` replication command.
+    ///
+    /// Must be called before replication is started on the connection.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `SystemIdentity` on success, or an `Error` on failure.
+    pub fn identify_system(&mut self) -> Result<SystemIdentity, Error> {
+        let rows = self.query("IDENTIFY_SYSTEM")?;
+        parse_identify_system(&rows)
+    }
+
+    /// Create a logical replication slot with the `CREATE_REPLICATION_SLOT` command.
+    ///
+    /// Uses the option syntax of PostgreSQL 15 and later, or the older keywords when the
+    /// server reports an earlier version.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot_name` - The name of the slot to create
+    /// * `options` - The output plugin and options of the slot
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the created `ReplicationSlot` on success, or an `Error`
+    /// on failure, which is `Error::InvalidReplicationCommand` if the slot name or the
+    /// output plugin is not made of lowercase letters, digits and underscores.
+    pub fn create_replication_slot(
+        &mut self,
+        slot_name: &str,
+        options: &CreateSlotOptions,
+    ) -> Result<ReplicationSlot, Error> {
+        let command = create_slot_command(slot_name, options, self.server_version_num())?;
+        let rows = self.query(&command)?;
+        parse_create_slot(&rows)
+    }
+
+    /// Drop a replication slot with the `DROP_REPLICATION_SLOT` command.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot_name` - The name of the slot to drop
+    /// * `wait` - Whether to wait until the slot is no longer in use, instead of failing
+    ///   with `Error::ReplicationSlotInUse`
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating success or an `Error` on failure, which is
+    /// `Error::ReplicationSlotDoesNotExist` if there is no such slot.
+    pub fn drop_replication_slot(&mut self, slot_name: &str, wait: bool) -> Result<(), Error> {
+        check_name("slot name", slot_name)?;
+        let mut command = format!("DROP_REPLICATION_SLOT {}", slot_name);
+        if wait {
+            command.push_str(" WAIT");
+        }
+
+        self.query(&command).map_err(slot_error)?;
+        Ok(())
+    }
+
+    /// Read information about a replication slot with the `READ_REPLICATION_SLOT` command.
+    ///
+    /// Requires PostgreSQL 15, and the server only supports the command for physical slots.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot_name` - The name of the slot to read
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `ReplicationSlotInfo`, or `None` if there is no
+    /// such slot, on success, or an `Error` on failure.
+    pub fn read_replication_slot(
+        &mut self,
+        slot_name: &str,
+    ) -> Result<Option<ReplicationSlotInfo>, Error> {
+        check_name("slot name", slot_name)?;
+        if self.server_version_num().unwrap_or(u32::MAX) < 150000 {
+            return Err(Error::UnsupportedServerVersion(
+                "READ_REPLICATION_SLOT requires PostgreSQL 15".to_string(),
+            ));
+        }
+
+        let rows = self
+            .query(&format!("READ_REPLICATION_SLOT {}", slot_name))
+            .map_err(slot_error)?;
+        parse_read_slot(&rows)
+    }
+
+    /// Change the options of a replication slot with the `ALTER_REPLICATION_SLOT` command.
+    ///
+    /// Requires PostgreSQL 17, or PostgreSQL 18 to change `two_phase`.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot_name` - The name of the slot to change
+    /// * `options` - The options to change
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating success or an `Error` on failure, which is
+    /// `Error::ReplicationSlotDoesNotExist` if there is no such slot,
+    /// `Error::ReplicationSlotInUse` if another process is using it or
+    /// `Error::InvalidReplicationCommand` if no option is set.
+    pub fn alter_replication_slot(
+        &mut self,
+        slot_name: &str,
+        options: &AlterSlotOptions,
+    ) -> Result<(), Error> {
+        let command = alter_slot_command(slot_name, options, self.server_version_num())?;
+        self.query(&command).map_err(slot_error)?;
+        Ok(())
+    }
+}
+
+// Give the server errors for missing and busy slots their own variants
+fn slot_error(err: Error) -> Error {
+    match err {
+        Error::Db(err) if err.code == "42704" => Error::ReplicationSlotDoesNotExist(err),
+        Error::Db(err) if err.code == "55006" => Error::ReplicationSlotInUse(err),
+        err => err,
+    }
+}
+
+// Check that a name can be put in a replication command as is, the server only accepts
+// lowercase letters, digits and underscores in slot names
+fn check_name(kind: &str, name: &str) -> Result<(), Error> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(Error::InvalidReplicationCommand(format!(
+            "{} \"{}\" must be 1 to {} characters long",
+            kind, name, MAX_NAME_LENGTH
+        )));
+    }
+    if !name
+        .bytes()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+    {
+        return Err(Error::InvalidReplicationCommand(format!(
+            "{} \"{}\" may only contain lowercase letters, digits and underscores",
+            kind, name
+        )));
+    }
+
+    Ok(())
+}
+
+// Build the CREATE_REPLICATION_SLOT command for a server version, assuming a recent
+// server if the version is unknown
+fn create_slot_command(
+    slot_name: &str,
+    options: &CreateSlotOptions,
+    server_version: Option<u32>,
+) -> Result<String, Error> {
+    check_name("slot name", slot_name)?;
+    check_name("output plugin", &options.output_plugin)?;
+    let server_version = server_version.unwrap_or(u32::MAX);
+
+    if options.failover && server_version < 170000 {
+        return Err(Error::UnsupportedServerVersion(
+            "FAILOVER slots require PostgreSQL 17".to_string(),
+        ));
+    }
+    if options.two_phase && server_version < 140000 {
+        return Err(Error::UnsupportedServerVersion(
+            "TWO_PHASE slots require PostgreSQL 14".to_string(),
+        ));
+    }
+
+    let mut command = format!("CREATE_REPLICATION_SLOT {}", slot_name);
+    if options.temporary {
+        command.push_str(" TEMPORARY");
+    }
+    command.push_str(" LOGICAL ");
+    command.push_str(&options.output_plugin);
+
+    if server_version >= 150000 {
+        let mut slot_options = Vec::new();
+        if let Some(snapshot) = options.snapshot {
+            slot_options.push(format!("SNAPSHOT '{}'", snapshot.as_str()));
+        }
+        if options.two_phase {
+            slot_options.push("TWO_PHASE".to_string());
+        }
+        if options.failover {
+            slot_options.push("FAILOVER".to_string());
+        }
+
+        if !slot_options.is_empty() {
+            command.push_str(&format!(" ({})", slot_options.join(", ")));
+        }
+    } else {
+        if let Some(snapshot) = options.snapshot {
+            command.push(' ');
+            command.push_str(snapshot.legacy_keyword());
+        }
+        if options.two_phase {
+            command.push_str(" TWO_PHASE");
+        }
+    }
+
+    Ok(command)
+}
+
+// Build the ALTER_REPLICATION_SLOT command for a server version, assuming a recent
+// server if the version is unknown
+fn alter_slot_command(
+    slot_name: &str,
+    options: &AlterSlotOptions,
+    server_version: Option<u32>,
+) -> Result<String, Error> {
+    check_name("slot name", slot_name)?;
+    if options.failover.is_none() && options.two_phase.is_none() {
+        return Err(Error::InvalidReplicationCommand(
+            "ALTER_REPLICATION_SLOT needs at least one option to change".to_string(),
+        ));
+    }
+    let server_version = server_version.unwrap_or(u32::MAX);
+
+    if server_version < 170000 {
+        return Err(Error::UnsupportedServerVersion(
+            "ALTER_REPLICATION_SLOT requires PostgreSQL 17".to_string(),
+        ));
+    }
+    if options.two_phase.is_some() && server_version < 180000 {
+        return Err(Error::UnsupportedServerVersion(
+            "Changing TWO_PHASE of a slot requires PostgreSQL 18".to_string(),
+        ));
+    }
+
+    let mut slot_options = Vec::new();
+    if let Some(failover) = options.failover {
+        slot_options.push(format!("FAILOVER {}", failover));
+    }
+    if let Some(two_phase) = options.two_phase {
+        slot_options.push(format!("TWO_PHASE {}", two_phase));
+    }
+
+    Ok(format!(
+        "ALTER_REPLICATION_SLOT {} ({})",
+        slot_name,
+        slot_options.join(", ")
+    ))
+}
+
+// Build a ReplicationSlotInfo from the row READ_REPLICATION_SLOT returns, which is all
+// nulls if the slot does not exist
+fn parse_read_slot(rows: &[Vec<Option<String>>]) -> Result<Option<ReplicationSlotInfo>, Error> {
+    let row = single_row(rows, "READ_REPLICATION_SLOT")?;
+
+    let slot_type = match row.first().and_then(Option::as_deref) {
+        None => return Ok(None),
+        Some("physical") => SlotType::Physical,
+        Some("logical") => SlotType::Logical,
+        Some(other) => {
+            return Err(Error::ReplicationProtocolViolation(format!(
+                "Unknown slot type: {}",
+                other
+            )));
+        }
+    };
+
+    let restart_lsn = match row.get(1).and_then(Option::as_deref) {
+        Some(lsn) => Some(lsn.parse()?),
+        None => None,
+    };
+    let restart_tli = match row.get(2).and_then(Option::as_deref) {
+        Some(tli) => Some(tli.parse()?),
+        None => None,
+    };
+
+    Ok(Some(ReplicationSlotInfo {
+        slot_type,
+        restart_lsn,
+        restart_tli,
+    }))
+}
+
+// Build a ReplicationSlot from the single row CREATE_REPLICATION_SLOT returns
+fn parse_create_slot(rows: &[Vec<Option<String>>]) -> Result<ReplicationSlot, Error> {
+    let row = single_row(rows, "CREATE_REPLICATION_SLOT")?;
+
+    Ok(ReplicationSlot {
+        slot_name: required_column(row, 0, "slot_name")?.to_string(),
+        consistent_point: required_column(row, 1, "consistent_point")?.parse()?,
+        snapshot_name: row.get(2).cloned().flatten(),
+        output_plugin: row.get(3).cloned().flatten(),
+    })
+}
+
+// Build a SystemIdentity from the single row IDENTIFY_SYSTEM returns
+fn parse_identify_system(rows: &[Vec<Option<String>>]) -> Result<SystemIdentity, Error> {
+    let row = single_row(rows, "IDENTIFY_SYSTEM")?;
+
+    Ok(SystemIdentity {
+        system_id: required_column(row, 0, "systemid")?.parse()?,
+        timeline: required_column(row, 1, "timeline")?.parse()?,
+        xlogpos: required_column(row, 2, "xlogpos")?.parse()?,
+        dbname: row.get(3).cloned().flatten(),
+    })
+}
+
+// Get the only row of a replication command result
+fn single_row<'a>(
+    rows: &'a [Vec<Option<String>>],
+    command: &str,
+) -> Result<&'a [Option<String>], Error> {
+    match rows {
+        [row] => Ok(row),
+        _ => Err(Error::ReplicationProtocolViolation(format!(
+            "Expected one row from {}, got {}",
+            command,
+            rows.len()
+        ))),
+    }
+}
+
+// Get a column of a replication command result that may not be null
+fn required_column<'a>(
+    row: &'a [Option<String>],
+    index: usize,
+    name: &str,
+) -> Result<&'a str, Error> {
+    row.get(index)
+        .and_then(Option::as_deref)
+        .ok_or_else(|| Error::ReplicationProtocolViolation(format!("Missing column {}", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DbError;
+
+    #[test]
+    fn test_parse_identify_system() {
+        let row = vec![
+            Some("7318253911212342016".to_string()),
+            Some("1".to_string()),
+            Some("0/1A2B3C4".to_string()),
+            Some("testing".to_string()),
+        ];
+
+        assert_eq!(
+            parse_identify_system(std::slice::from_ref(&row)).unwrap(),
+            SystemIdentity {
+                system_id: 7318253911212342016,
+                timeline: 1,
+                xlogpos: Lsn(0x1A2B3C4),
+                dbname: Some("testing".to_string()),
+            }
+        );
+
+        // Physical replication connections have no database
+        let physical = vec![row[0].clone(), row[1].clone(), row[2].clone(), None];
+        assert_eq!(parse_identify_system(&[physical]).unwrap().dbname, None);
+
+        assert!(parse_identify_system(&[]).is_err());
+        assert!(parse_identify_system(&[vec![None]]).is_err());
+    }
+
+    #[test]
+    fn test_create_slot_command() {
+        let command = |options: &CreateSlotOptions, version| {
+            create_slot_command("my_slot", options, Some(version))
+        };
+
+        let defaults = CreateSlotOptions::new();
+        assert_eq!(
+            command(&defaults, 170000).unwrap(),
+            "CREATE_REPLICATION_SLOT my_slot LOGICAL pgoutput"
+        );
+
+        let options = CreateSlotOptions::new()
+            .temporary(true)
+            .two_phase(true)
+            .snapshot(SnapshotAction::Nothing);
+        assert_eq!(
+            command(&options, 150000).unwrap(),
+            "CREATE_REPLICATION_SLOT my_slot TEMPORARY LOGICAL pgoutput (SNAPSHOT 'nothing', TWO_PHASE)"
+        );
+        assert_eq!(
+            command(&options, 140000).unwrap(),
+            "CREATE_REPLICATION_SLOT my_slot TEMPORARY LOGICAL pgoutput NOEXPORT_SNAPSHOT TWO_PHASE"
+        );
+        assert!(matches!(
+            command(&options, 130000),
+            Err(Error::UnsupportedServerVersion(_))
+        ));
+
+        let options = CreateSlotOptions::new()
+            .output_plugin("test_decoding")
+            .failover(true)
+            .snapshot(SnapshotAction::Export);
+        assert_eq!(
+            command(&options, 170000).unwrap(),
+            "CREATE_REPLICATION_SLOT my_slot LOGICAL test_decoding (SNAPSHOT 'export', FAILOVER)"
+        );
+        assert!(matches!(
+            command(&options, 160000),
+            Err(Error::UnsupportedServerVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_alter_slot_command() {
+        let options = AlterSlotOptions::new().failover(true);
+        assert_eq!(
+            alter_slot_command("my_slot", &options, Some(170000)).unwrap(),
+            "ALTER_REPLICATION_SLOT my_slot (FAILOVER true)"
+        );
+        assert!(matches!(
+            alter_slot_command("my_slot", &options, Some(160000)),
+            Err(Error::UnsupportedServerVersion(_))
+        ));
+
+        let options = options.two_phase(false);
+        assert_eq!(
+            alter_slot_command("my_slot", &options, Some(180000)).unwrap(),
+            "ALTER_REPLICATION_SLOT my_slot (FAILOVER true, TWO_PHASE false)"
+        );
+        assert!(matches!(
+            alter_slot_command("my_slot", &options, Some(170000)),
+            Err(Error::UnsupportedServerVersion(_))
+        ));
+
+        assert!(matches!(
+            alter_slot_command("my_slot", &AlterSlotOptions::new(), Some(180000)),
+            Err(Error::InvalidReplicationCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_name() {
+        assert!(check_name("slot name", "my_slot_2").is_ok());
+        assert!(check_name("slot name", &"a".repeat(63)).is_ok());
+
+        for name in ["", "My_Slot", "my-slot", "my slot", "s; DROP", "slöt"] {
+            assert!(
+                matches!(
+                    check_name("slot name", name),
+                    Err(Error::InvalidReplicationCommand(_))
+                ),
+                "Expected {:?} to be rejected",
+                name
+            );
+        }
+        assert!(check_name("slot name", &"a".repeat(64)).is_err());
+
+        let options = CreateSlotOptions::new().output_plugin("pgoutput'");
+        assert!(matches!(
+            create_slot_command("my_slot", &options, Some(170000)),
+            Err(Error::InvalidReplicationCommand(_))
+        ));
+        assert!(matches!(
+            alter_slot_command(
+                "My_Slot",
+                &AlterSlotOptions::new().failover(true),
+                Some(170000)
+            ),
+            Err(Error::InvalidReplicationCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_read_slot() {
+        let row = vec![
+            Some("physical".to_string()),
+            Some("0/3000000".to_string()),
+            Some("1".to_string()),
+        ];
+        assert_eq!(
+            parse_read_slot(&[row]).unwrap(),
+            Some(ReplicationSlotInfo {
+                slot_type: SlotType::Physical,
+                restart_lsn: Some(Lsn(0x3000000)),
+                restart_tli: Some(1),
+            })
+        );
+
+        let missing = vec![None, None, None];
+        assert_eq!(parse_read_slot(&[missing]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_slot_error() {
+        let db_error = |code: &str| {
+            Error::Db(Box::new(DbError {
+                code: code.to_string(),
+                ..DbError::default()
+            }))
+        };
+
+        assert!(matches!(
+            slot_error(db_error("42704")),
+            Error::ReplicationSlotDoesNotExist(_)
+        ));
+        assert!(matches!(
+            slot_error(db_error("55006")),
+            Error::ReplicationSlotInUse(_)
+        ));
+        assert!(matches!(slot_error(db_error("42601")), Error::Db(_)));
+        assert_eq!(slot_error(db_error("55006")).code(), Some("55006"));
+    }
+
+    #[test]
+    fn test_parse_create_slot() {
+        let row = vec![
+            Some("my_slot".to_string()),
+            Some("0/1A2B3C4".to_string()),
+            Some("00000003-00000002-1".to_string()),
+            Some("pgoutput".to_string()),
+        ];
+
+        assert_eq!(
+            parse_create_slot(&[row]).unwrap(),
+            ReplicationSlot {
+                slot_name: "my_slot".to_string(),
+                consistent_point: Lsn(0x1A2B3C4),
+                snapshot_name: Some("00000003-00000002-1".to_string()),
+                output_plugin: Some("pgoutput".to_string()),
+            }
+        );
+    }
+}
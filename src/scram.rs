@@ -0,0 +1,337 @@
+use crate::Error;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+// SASL mechanism names advertised by the server in AuthenticationSASL
+pub(crate) const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
+pub(crate) const SCRAM_SHA_256_PLUS: &str = "SCRAM-SHA-256-PLUS";
+
+// Length of the raw client nonce before base64 encoding
+const NONCE_LENGTH: usize = 18;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Client side of a SCRAM-SHA-256 exchange (RFC 5802 / RFC 7677).
+///
+/// The exchange is driven by the connection: `client_first_message` is sent in the
+/// SASLInitialResponse, the server's reply to it is passed to `client_final_message`,
+/// and the server's final message is checked with `verify_server_final`.
+pub(crate) struct ScramSha256 {
+    password: Vec<u8>,
+    client_nonce: String,
+    channel_binding: ScramChannelBinding,
+    state: State,
+}
+
+/// How the client takes part in channel binding, which decides the GS2 header.
+pub(crate) enum ScramChannelBinding {
+    /// The client does not support channel binding ("n").
+    Unsupported,
+    /// The client supports channel binding but the server did not offer it ("y").
+    NotOffered,
+    /// SCRAM-SHA-256-PLUS with the hash of the server certificate ("p=tls-server-end-point").
+    TlsServerEndPoint(Vec<u8>),
+}
+
+enum State {
+    Initial,
+    ClientFirstSent,
+    ClientFinalSent {
+        salted_password: [u8; 32],
+        auth_message: String,
+    },
+    Done,
+}
+
+impl ScramSha256 {
+    pub fn new(password: &str, channel_binding: ScramChannelBinding) -> Self {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::rng().fill(&mut nonce[..]);
+
+        Self::with_nonce(password, channel_binding, BASE64.encode(nonce))
+    }
+
+    fn with_nonce(
+        password: &str,
+        channel_binding: ScramChannelBinding,
+        client_nonce: String,
+    ) -> Self {
+        // The password is used as-is. PostgreSQL runs SASLprep on the password, which
+        // is a no-op for ASCII passwords, and falls back to the raw bytes when it fails.
+        ScramSha256 {
+            password: password.as_bytes().to_vec(),
+            client_nonce,
+            channel_binding,
+            state: State::Initial,
+        }
+    }
+
+    // The GS2 header, signalling whether and how channel binding is used
+    fn gs2_header(&self) -> &'static str {
+        match self.channel_binding {
+            ScramChannelBinding::Unsupported => "n,,",
+            ScramChannelBinding::NotOffered => "y,,",
+            ScramChannelBinding::TlsServerEndPoint(_) => "p=tls-server-end-point,,",
+        }
+    }
+
+    // The c= attribute: the GS2 header followed by the channel binding data
+    fn channel_binding_attribute(&self) -> String {
+        let mut input = self.gs2_header().as_bytes().to_vec();
+        if let ScramChannelBinding::TlsServerEndPoint(hash) = &self.channel_binding {
+            input.extend_from_slice(hash);
+        }
+        BASE64.encode(input)
+    }
+
+    // PostgreSQL ignores the username in the SCRAM exchange and uses the one from
+    // the startup packet, so it is left empty just like libpq does.
+    fn client_first_message_bare(&self) -> String {
+        format!("n=,r={}", self.client_nonce)
+    }
+
+    /// Build the client-first-message sent in the SASLInitialResponse.
+    pub fn client_first_message(&mut self) -> String {
+        self.state = State::ClientFirstSent;
+        format!("{}{}", self.gs2_header(), self.client_first_message_bare())
+    }
+
+    /// Process the server-first-message and build the client-final-message.
+    pub fn client_final_message(&mut self, server_first: &[u8]) -> Result<String, Error> {
+        if !matches!(self.state, State::ClientFirstSent) {
+            return Err(Error::InvalidScramMessage(
+                "unexpected server-first-message",
+            ));
+        }
+
+        let server_first = std::str::from_utf8(server_first)?;
+
+        let mut nonce = None;
+        let mut salt = None;
+        let mut iterations = None;
+
+        for attribute in server_first.split(',') {
+            if let Some(value) = attribute.strip_prefix("r=") {
+                nonce = Some(value);
+            } else if let Some(value) = attribute.strip_prefix("s=") {
+                salt = Some(value);
+            } else if let Some(value) = attribute.strip_prefix("i=") {
+                iterations = Some(value);
+            }
+        }
+
+        let nonce = nonce.ok_or(Error::InvalidScramMessage("missing nonce"))?;
+        let salt = salt.ok_or(Error::InvalidScramMessage("missing salt"))?;
+        let iterations = iterations.ok_or(Error::InvalidScramMessage("missing iteration count"))?;
+
+        // The server nonce must extend the nonce we sent
+        if !nonce.starts_with(&self.client_nonce) || nonce.len() == self.client_nonce.len() {
+            return Err(Error::InvalidScramMessage("server nonce mismatch"));
+        }
+
+        let salt = BASE64
+            .decode(salt)
+            .map_err(|_| Error::InvalidScramMessage("invalid salt encoding"))?;
+        let iterations: u32 = iterations.parse()?;
+        if iterations == 0 {
+            return Err(Error::InvalidScramMessage("invalid iteration count"));
+        }
+
+        let salted_password = hi(&self.password, &salt, iterations);
+
+        let client_key = hmac(&salted_password, b"Client Key");
+        let stored_key: [u8; 32] = Sha256::digest(client_key).into();
+
+        let client_final_without_proof =
+            format!("c={},r={}", self.channel_binding_attribute(), nonce);
+
+        let auth_message = format!(
+            "{},{},{}",
+            self.client_first_message_bare(),
+            server_first,
+            client_final_without_proof
+        );
+
+        let client_signature = hmac(&stored_key, auth_message.as_bytes());
+
+        let mut client_proof = client_key;
+        for (proof, signature) in client_proof.iter_mut().zip(client_signature.iter()) {
+            *proof ^= signature;
+        }
+
+        self.state = State::ClientFinalSent {
+            salted_password,
+            auth_message,
+        };
+
+        Ok(format!(
+            "{},p={}",
+            client_final_without_proof,
+            BASE64.encode(client_proof)
+        ))
+    }
+
+    /// Verify the server signature in the server-final-message.
+    pub fn verify_server_final(&mut self, server_final: &[u8]) -> Result<(), Error> {
+        let State::ClientFinalSent {
+            salted_password,
+            auth_message,
+        } = std::mem::replace(&mut self.state, State::Done)
+        else {
+            return Err(Error::InvalidScramMessage(
+                "unexpected server-final-message",
+            ));
+        };
+
+        let server_final = std::str::from_utf8(server_final)?;
+
+        if let Some(error) = server_final.strip_prefix("e=") {
+            return Err(Error::Authentication(format!(
+                "SCRAM authentication rejected by server: {}",
+                error
+            )));
+        }
+
+        let verifier = server_final
+            .split(',')
+            .find_map(|attribute| attribute.strip_prefix("v="))
+            .ok_or(Error::InvalidScramMessage("missing server signature"))?;
+
+        let verifier = BASE64
+            .decode(verifier)
+            .map_err(|_| Error::InvalidScramMessage("invalid server signature encoding"))?;
+
+        let server_key = hmac(&salted_password, b"Server Key");
+
+        let mut mac = HmacSha256::new_from_slice(&server_key).expect("HMAC accepts any key length");
+        mac.update(auth_message.as_bytes());
+
+        // verify_slice compares in constant time
+        mac.verify_slice(&verifier)
+            .map_err(|_| Error::ScramServerSignatureMismatch)
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+// Hi() from RFC 5802, which is PBKDF2 with HMAC-SHA-256 and a single output block
+fn hi(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(password).expect("HMAC accepts any key length");
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());
+
+    let mut previous: [u8; 32] = mac.finalize().into_bytes().into();
+    let mut result = previous;
+
+    for _ in 1..iterations {
+        previous = hmac(password, &previous);
+        for (r, p) in result.iter_mut().zip(previous.iter()) {
+            *r ^= p;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT_NONCE: &str = "rOprNGfwEbeRWgbNEkqO";
+    const SERVER_FIRST: &str =
+        "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096";
+
+    #[test]
+    fn test_scram_exchange() {
+        let mut scram = ScramSha256::with_nonce(
+            "pencil",
+            ScramChannelBinding::Unsupported,
+            CLIENT_NONCE.to_string(),
+        );
+
+        assert_eq!(scram.client_first_message(), "n,,n=,r=rOprNGfwEbeRWgbNEkqO");
+
+        let client_final = scram.client_final_message(SERVER_FIRST.as_bytes()).unwrap();
+        assert_eq!(
+            client_final,
+            "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=qvT2SWdEH5Q06albL+hjSYuUhCG7VndFyzIb7CK4n9k="
+        );
+
+        scram
+            .verify_server_final(b"v=3HO6Qt1M4MKJrmlKaoOqLAI0/0TV0HZe7J9H3MBtSOg=")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_scram_channel_binding_not_offered() {
+        let mut scram = ScramSha256::with_nonce(
+            "pencil",
+            ScramChannelBinding::NotOffered,
+            CLIENT_NONCE.to_string(),
+        );
+
+        assert_eq!(scram.client_first_message(), "y,,n=,r=rOprNGfwEbeRWgbNEkqO");
+
+        let client_final = scram.client_final_message(SERVER_FIRST.as_bytes()).unwrap();
+        assert_eq!(
+            client_final,
+            "c=eSws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=VpuC5DGQa5ro9tXE9MnKs69NH1nxnuregZZcclqIGfM="
+        );
+    }
+
+    #[test]
+    fn test_scram_tls_server_end_point() {
+        let certificate_hash = Sha256::digest(b"certificate").to_vec();
+        let mut scram = ScramSha256::with_nonce(
+            "pencil",
+            ScramChannelBinding::TlsServerEndPoint(certificate_hash),
+            CLIENT_NONCE.to_string(),
+        );
+
+        assert_eq!(
+            scram.client_first_message(),
+            "p=tls-server-end-point,,n=,r=rOprNGfwEbeRWgbNEkqO"
+        );
+
+        let client_final = scram.client_final_message(SERVER_FIRST.as_bytes()).unwrap();
+        assert_eq!(
+            client_final,
+            "c=cD10bHMtc2VydmVyLWVuZC1wb2ludCwsA9Zt0Ig1wco/EozOrNHzGslBYwlrIPRFroQoW8CDLXI=,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=+zCdMK4l2JRBX6aaOY99rhAluhUDgC1judMOSldbSW0="
+        );
+    }
+
+    #[test]
+    fn test_scram_server_signature_mismatch() {
+        let mut scram = ScramSha256::with_nonce(
+            "pencil",
+            ScramChannelBinding::Unsupported,
+            CLIENT_NONCE.to_string(),
+        );
+        scram.client_first_message();
+        scram.client_final_message(SERVER_FIRST.as_bytes()).unwrap();
+
+        let result = scram.verify_server_final(b"v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=");
+        assert!(matches!(result, Err(Error::ScramServerSignatureMismatch)));
+    }
+
+    #[test]
+    fn test_scram_server_nonce_mismatch() {
+        let mut scram = ScramSha256::with_nonce(
+            "pencil",
+            ScramChannelBinding::Unsupported,
+            CLIENT_NONCE.to_string(),
+        );
+        scram.client_first_message();
+
+        let result =
+            scram.client_final_message(b"r=somethingElse,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096");
+        assert!(matches!(result, Err(Error::InvalidScramMessage(_))));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::Error;
+use crate::config::ConnectOptions;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+/// A stream whose read timeout can be read and changed.
+///
+/// `Subscriber` sets a read timeout on a stream that has none, so that it wakes up to
+/// send status updates and to notice a server that went silent while no data arrives.
+pub trait SetReadTimeout {
+    /// Get the read timeout of the stream, `None` if reads block until data arrives.
+    fn read_timeout(&self) -> io::Result<Option<Duration>>;
+
+    /// Set the read timeout of the stream, `None` to block until data arrives.
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl SetReadTimeout for TcpStream {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        TcpStream::read_timeout(self)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl SetReadTimeout for UnixStream {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        UnixStream::read_timeout(self)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+/// A socket opened by `ConnectOptions::connect`.
+///
+/// Hosts starting with `/` are Unix domain socket directories, everything else is
+/// resolved and connected to over TCP.
+#[derive(Debug)]
+pub enum Socket {
+    /// A TCP connection.
+    Tcp(TcpStream),
+    /// A Unix domain socket connection.
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Socket {
+    // Open a socket to the host and port in the options
+    pub(crate) fn connect(options: &ConnectOptions) -> Result<Socket, Error> {
+        let host = match options.first_host() {
+            "" => "localhost",
+            host => host,
+        };
+
+        if host.starts_with('/') {
+            return Self::connect_unix(host, options);
+        }
+
+        let mut last_error = None;
+
+        // Try every address the host resolves to, e.g. both ::1 and 127.0.0.1 for localhost
+        for address in (host, options.first_port()).to_socket_addrs()? {
+            let result = match options.connect_timeout {
+                Some(timeout) => TcpStream::connect_timeout(&address, timeout),
+                None => TcpStream::connect(address),
+            };
+
+            match result {
+                Ok(stream) => {
+                    stream.set_nodelay(true)?;
+                    stream.set_read_timeout(options.read_timeout)?;
+                    return Ok(Socket::Tcp(stream));
+                }
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        Err(Error::Io(last_error.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("could not resolve host: {}", host),
+            )
+        })))
+    }
+
+    #[cfg(unix)]
+    fn connect_unix(directory: &str, options: &ConnectOptions) -> Result<Socket, Error> {
+        let path = format!(
+            "{}/.s.PGSQL.{}",
+            directory.trim_end_matches('/'),
+            options.first_port()
+        );
+        let stream = UnixStream::connect(path)?;
+        stream.set_read_timeout(options.read_timeout)?;
+        Ok(Socket::Unix(stream))
+    }
+
+    #[cfg(not(unix))]
+    fn connect_unix(directory: &str, _options: &ConnectOptions) -> Result<Socket, Error> {
+        Err(Error::Io(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("Unix domain sockets are not supported: {}", directory),
+        )))
+    }
+
+    // Whether this is a Unix domain socket, which never uses TLS
+    pub(crate) fn is_unix(&self) -> bool {
+        match self {
+            Socket::Tcp(_) => false,
+            #[cfg(unix)]
+            Socket::Unix(_) => true,
+        }
+    }
+}
+
+impl SetReadTimeout for Socket {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        match self {
+            Socket::Tcp(stream) => stream.read_timeout(),
+            #[cfg(unix)]
+            Socket::Unix(stream) => stream.read_timeout(),
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Socket::Tcp(stream) => stream.set_read_timeout(timeout),
+            #[cfg(unix)]
+            Socket::Unix(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+}
+
+impl Read for Socket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Socket::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Socket::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Socket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Socket::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Socket::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Socket::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Socket::Unix(stream) => stream.flush(),
+        }
+    }
+}
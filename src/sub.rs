@@ -1,10 +1,31 @@
 use crate::Error;
 use crate::conn::Connection;
-use crate::value::{Value, parse_binary_value, parse_text_value};
+use crate::error::DbError;
+use crate::event::Event;
+use crate::lsn::Lsn;
+use crate::socket::SetReadTimeout;
+use crate::value::{
+    PG_TYPE_TEXT, Value, builtin_type_id, has_text_parser, parse_binary_value, parse_text_value,
+};
+
+use jiff::Timestamp;
 
-use jiff;
 use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+// Microseconds between the Unix epoch and the PostgreSQL epoch, 2000-01-01
+const POSTGRES_EPOCH_MICROS: i64 = 946_684_800_000_000;
+
+// Option bits of a Truncate message
+const TRUNCATE_CASCADE: u8 = 1;
+const TRUNCATE_RESTART_IDENTITY: u8 = 2;
+
+// Flag of a logical decoding message that is part of a transaction
+const MESSAGE_TRANSACTIONAL: u8 = 1;
+
+// Default interval between standby status updates
+const DEFAULT_STATUS_INTERVAL: Duration = Duration::from_secs(10);
 
 // Helper functions for reading binary data directly from slices - make these private
 fn read_u64_from_slice(data: &[u8]) -> Result<u64, Error> {
@@ -27,36 +48,67 @@ fn read_i64_from_slice(data: &[u8]) -> Result<i64, Error> {
     Ok(value)
 }
 
-// Parse PostgreSQL error/notice message format - make this private
-fn parse_pg_error_message(data: &[u8]) -> Result<String, Error> {
-    let mut error_message = String::new();
-    let mut i = 0;
-
-    while i < data.len() {
-        let field_type = data[i];
-        i += 1;
+// Parse a time setting as SHOW prints it, e.g. "1min", "500ms" or "0"
+fn parse_duration_setting(value: &str) -> Option<Duration> {
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+    let number: u64 = number.parse().ok()?;
+
+    match unit.trim() {
+        "us" => Some(Duration::from_micros(number)),
+        "" | "ms" => Some(Duration::from_millis(number)),
+        "s" => Some(Duration::from_secs(number)),
+        "min" => Some(Duration::from_secs(number * 60)),
+        "h" => Some(Duration::from_secs(number * 60 * 60)),
+        "d" => Some(Duration::from_secs(number * 24 * 60 * 60)),
+        _ => None,
+    }
+}
 
-        if field_type == 0 {
-            break; // End of message
+// Build the START_REPLICATION command for the options, rejecting options the server
+// does not support
+fn start_replication_command(
+    options: &SubscriberOptions,
+    server_version: Option<u32>,
+) -> Result<String, Error> {
+    let server_version = server_version.unwrap_or(u32::MAX);
+
+    let mut plugin_options = vec![
+        "proto_version '1'".to_string(),
+        format!("publication_names '{}'", options.publication_name),
+    ];
+
+    if let Some(origin) = options.origin {
+        if server_version < 160000 {
+            return Err(Error::UnsupportedServerVersion(
+                "The origin option requires PostgreSQL 16".to_string(),
+            ));
         }
 
-        let mut field_value = Vec::new();
-        while i < data.len() && data[i] != 0 {
-            field_value.push(data[i]);
-            i += 1;
-        }
-        i += 1; // Skip null terminator
+        plugin_options.push(match origin {
+            OriginFilter::Any => "origin 'any'".to_string(),
+            OriginFilter::None => "origin 'none'".to_string(),
+        });
+    }
 
-        if let Ok(value) = std::str::from_utf8(&field_value) {
-            match field_type {
-                b'M' => error_message = value.to_string(), // Message
-                b'S' => error_message = format!("{}: {}", value, error_message), // Severity
-                _ => {}                                    // Ignore other fields
-            }
+    if options.messages {
+        if server_version < 140000 {
+            return Err(Error::UnsupportedServerVersion(
+                "Logical decoding messages require PostgreSQL 14".to_string(),
+            ));
         }
+
+        plugin_options.push("messages 'true'".to_string());
     }
 
-    Ok(error_message)
+    Ok(format!(
+        "START_REPLICATION SLOT {} LOGICAL {} ({})",
+        options.slot_name,
+        options.start_lsn,
+        plugin_options.join(", ")
+    ))
 }
 
 /// Represents a Write-Ahead Log (WAL) message from PostgreSQL logical replication.
@@ -65,8 +117,22 @@ fn parse_pg_error_message(data: &[u8]) -> Result<String, Error> {
 /// and are sent as part of the logical replication stream.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Message {
-    /// Begin transaction with the given Log Sequence Number (LSN).
-    Begin(u64), // LSN
+    /// Begin of a transaction.
+    Begin {
+        /// The location of the transaction's commit record.
+        final_lsn: Lsn,
+        /// The commit timestamp of the transaction.
+        commit_time: Timestamp,
+        /// The transaction ID.
+        xid: u32,
+    },
+    /// Origin of a transaction that was replayed from another node, sent after its Begin.
+    Origin {
+        /// The location of the commit record on the origin node.
+        commit_lsn: Lsn,
+        /// The name of the replication origin.
+        name: String,
+    },
     /// Relation (table) definition message.
     Relation {
         /// The OID of the relation.
@@ -103,8 +169,50 @@ pub enum Message {
         /// The old tuple data that was deleted (if available).
         old_tuple_data: Option<Vec<Option<Value>>>,
     },
-    /// Commit transaction with the given Log Sequence Number (LSN).
-    Commit(u64), // LSN
+    /// Type definition message, sent before the first row with a column of a type that
+    /// is not built in, e.g. an enum, a domain or an extension type.
+    Type {
+        /// The OID of the type.
+        id: u32,
+        /// The namespace (schema) name of the type, empty for `pg_catalog`, e.g. for the
+        /// base type of a domain over a built-in type.
+        namespace: String,
+        /// The name of the type, or of the base type of a domain.
+        name: String,
+    },
+    /// Truncate operation on one or more tables.
+    Truncate {
+        /// The OIDs of the relations (tables) that were truncated.
+        relation_ids: Vec<u32>,
+        /// Whether `CASCADE` was specified.
+        cascade: bool,
+        /// Whether `RESTART IDENTITY` was specified.
+        restart_identity: bool,
+    },
+    /// Commit of a transaction.
+    Commit {
+        /// Flags of the commit, currently unused and always 0.
+        flags: u8,
+        /// The location of the commit record.
+        commit_lsn: Lsn,
+        /// The location just after the commit record, to acknowledge with
+        /// `Subscriber::ack` once the transaction is processed.
+        end_lsn: Lsn,
+        /// The commit timestamp of the transaction.
+        commit_time: Timestamp,
+    },
+    /// Message written to the WAL with `pg_logical_emit_message`.
+    LogicalMessage {
+        /// Whether the message is part of a transaction, non-transactional messages are
+        /// sent as soon as they are decoded, outside of `Begin` and `Commit`.
+        transactional: bool,
+        /// The location of the message.
+        lsn: Lsn,
+        /// The prefix the message was emitted with.
+        prefix: String,
+        /// The content of the message.
+        content: Vec<u8>,
+    },
     /// Unknown message type with the raw message type byte.
     Unknown(u8),
 }
@@ -124,6 +232,18 @@ pub struct RelationInfo {
     pub replica_identity: u8,
 }
 
+/// Information about a PostgreSQL data type used in replication.
+///
+/// This struct holds the name of a type that is not built in, as announced by a
+/// `Message::Type` before the first row that uses it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeInfo {
+    /// The namespace (schema) name of the type, empty for `pg_catalog`.
+    pub namespace: String,
+    /// The name of the type, or of the base type of a domain.
+    pub name: String,
+}
+
 /// Represents a column in a PostgreSQL relation.
 ///
 /// Contains metadata about a table column including its name, type information,
@@ -140,21 +260,164 @@ pub struct Column {
     pub flags: u8, // Add flags field to track column attributes
 }
 
+/// Which changes to stream, by the origin of their transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OriginFilter {
+    /// Stream all changes, including those replayed from other nodes.
+    Any,
+    /// Stream only changes without an origin, i.e. made on the server itself.
+    None,
+}
+
+/// Options for starting logical replication with `Subscriber::with_options`.
+///
+/// ```rust
+/// use lolrepl::SubscriberOptions;
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), lolrepl::Error> {
+/// // Resume after the position a sink persisted, instead of the slot's confirmed position
+/// let options = SubscriberOptions::new("my_slot", "my_publication")
+///     .start_lsn("0/16B3748".parse()?)
+///     .receive_timeout(Duration::from_secs(120));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriberOptions {
+    slot_name: String,
+    publication_name: String,
+    start_lsn: Lsn,
+    status_interval: Duration,
+    request_reply: bool,
+    receive_timeout: Option<Duration>,
+    origin: Option<OriginFilter>,
+    messages: bool,
+    auto_ack: bool,
+}
+
+impl SubscriberOptions {
+    /// Create options to stream a publication from a replication slot.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot_name` - The name of the replication slot to use
+    /// * `publication_name` - The name of the publication to subscribe to
+    pub fn new(slot_name: &str, publication_name: &str) -> Self {
+        SubscriberOptions {
+            slot_name: slot_name.to_string(),
+            publication_name: publication_name.to_string(),
+            start_lsn: Lsn::INVALID,
+            status_interval: DEFAULT_STATUS_INTERVAL,
+            request_reply: false,
+            receive_timeout: None,
+            origin: None,
+            messages: false,
+            auto_ack: false,
+        }
+    }
+
+    /// Set the location to start streaming from.
+    ///
+    /// The server starts at the slot's confirmed flush location if it is later, so
+    /// this can only skip ahead. The default of `Lsn::INVALID` resumes from the slot.
+    pub fn start_lsn(mut self, start_lsn: Lsn) -> Self {
+        self.start_lsn = start_lsn;
+        self
+    }
+
+    /// Set the interval between status updates sent to the server, 10 seconds by default.
+    ///
+    /// The interval is shortened to half the server's `wal_sender_timeout`, so the
+    /// server does not end the connection as long as `Subscriber::next` is called often
+    /// enough.
+    pub fn status_interval(mut self, status_interval: Duration) -> Self {
+        self.status_interval = status_interval;
+        self
+    }
+
+    /// Ask the server to answer each status update with a keepalive message.
+    pub fn request_reply(mut self, request_reply: bool) -> Self {
+        self.request_reply = request_reply;
+        self
+    }
+
+    /// Fail with `Error::ReplicationStreamTimedOut` when the server sends nothing for longer
+    /// than the timeout.
+    ///
+    /// After half the timeout without messages, a status update asks the server for a
+    /// reply, so an idle but healthy server is not mistaken for a silent one. Only time
+    /// spent waiting for data counts, so a consumer that takes long between calls to
+    /// `Subscriber::next` does not time out while data is waiting to be read.
+    pub fn receive_timeout(mut self, receive_timeout: Duration) -> Self {
+        self.receive_timeout = Some(receive_timeout);
+        self
+    }
+
+    /// Filter the changes to stream by their origin, e.g. to break loops between nodes
+    /// that replicate to each other.
+    ///
+    /// By default the server streams changes of any origin. Filtering requires
+    /// PostgreSQL 16, `Subscriber::with_options` fails with
+    /// `Error::UnsupportedServerVersion` on older servers.
+    pub fn origin(mut self, origin: OriginFilter) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Stream messages written with `pg_logical_emit_message` as `Message::LogicalMessage`.
+    ///
+    /// Messages require PostgreSQL 14, `Subscriber::with_options` fails with
+    /// `Error::UnsupportedServerVersion` on older servers.
+    pub fn messages(mut self, messages: bool) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Acknowledge every location as soon as it is received, as if `Subscriber::ack`
+    /// was called with it.
+    ///
+    /// This is how earlier versions behaved. Changes that were received but not yet
+    /// persisted by the application are lost if it crashes, so only enable this for
+    /// consumers that can afford to miss changes.
+    pub fn auto_ack(mut self, auto_ack: bool) -> Self {
+        self.auto_ack = auto_ack;
+        self
+    }
+}
+
 /// A PostgreSQL logical replication subscriber.
 ///
 /// This struct manages a replication connection and handles the streaming of
 /// logical replication data from PostgreSQL. It maintains relation metadata
 /// and provides methods to consume WAL messages.
+///
+/// The slot only advances past locations acknowledged with `ack`. Until then the server
+/// keeps the WAL after the slot's position, so a consumer that never calls `ack` makes
+/// the WAL grow until the server's disk fills up. Earlier versions acknowledged every
+/// received location, `SubscriberOptions::auto_ack` restores that behavior.
+///
+/// The subscriber has no background thread: status updates, which keep the server from
+/// ending the connection after `wal_sender_timeout`, are only sent from `next`. Call it
+/// at least once per status interval, also while processing a large transaction.
+///
+/// Streaming requires a stream that implements `SetReadTimeout`, which the streams of
+/// this crate, `TcpStream` and `UnixStream` do. This is a breaking change for code that
+/// streamed over its own `Read + Write` type, which now has to implement the trait too.
 pub struct Subscriber<T: Read + Write> {
     connection: Connection<T>,
-    slot_name: String,
-    publication_name: String,
+    options: SubscriberOptions,
     relation_cache: HashMap<u32, RelationInfo>,
-    last_received_lsn: u64,
-    last_status_update: std::time::Instant,
+    type_cache: HashMap<u32, TypeInfo>,
+    received_lsn: Lsn,
+    flushed_lsn: Lsn,
+    status_interval: Duration,
+    last_status_update: Instant,
+    last_received: Instant,
+    reply_requested_at: Option<Instant>,
 }
 
-impl<T: Read + Write> Subscriber<T> {
+impl<T: Read + Write + SetReadTimeout> Subscriber<T> {
     /// Create a new subscriber with an existing replication connection.
     ///
     /// This method initializes a new `Subscriber` and starts the replication
@@ -173,28 +436,121 @@ impl<T: Read + Write> Subscriber<T> {
         connection: Connection<T>,
         slot_name: &str,
         publication_name: &str,
+    ) -> Result<Self, Error> {
+        Self::with_options(
+            connection,
+            SubscriberOptions::new(slot_name, publication_name),
+        )
+    }
+
+    /// Create a new subscriber with an existing replication connection and options.
+    ///
+    /// This method initializes a new `Subscriber` and starts the replication
+    /// process with the slot, publication and start location in the options.
+    ///
+    /// If the stream has no read timeout, it is set to the status interval, or half the
+    /// receive timeout if that is shorter, and stays set for the rest of the connection.
+    /// A read timeout the caller set, e.g. with `ConnectOptions::read_timeout`, is kept,
+    /// so status updates and the receive timeout are only checked as often as it allows.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - An established `Connection`
+    /// * `options` - The `SubscriberOptions` to start replication with
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Subscriber` on success, or an `Error` on failure.
+    pub fn with_options(
+        connection: Connection<T>,
+        options: SubscriberOptions,
     ) -> Result<Self, Error> {
         let mut subscriber = Subscriber {
             connection,
-            slot_name: slot_name.to_string(),
-            publication_name: publication_name.to_string(),
+            status_interval: options.status_interval,
+            options,
             relation_cache: HashMap::new(),
-            last_received_lsn: 0,
-            last_status_update: std::time::Instant::now(),
+            type_cache: HashMap::new(),
+            received_lsn: Lsn::INVALID,
+            flushed_lsn: Lsn::INVALID,
+            last_status_update: Instant::now(),
+            last_received: Instant::now(),
+            reply_requested_at: None,
         };
 
+        subscriber.limit_status_interval()?;
         subscriber.start_replication()?;
 
+        // Wake up in time for status updates and the receive timeout while no data
+        // arrives, unless the caller chose a read timeout
+        let stream = subscriber.connection.stream();
+        if stream.read_timeout()?.is_none() {
+            stream.set_read_timeout(Some(subscriber.read_timeout()))?;
+        }
+
         Ok(subscriber)
     }
 
+    // The read timeout of the stream while streaming, never zero as that is rejected
+    fn read_timeout(&self) -> Duration {
+        let timeout = match self.options.receive_timeout {
+            Some(receive_timeout) => self.status_interval.min(receive_timeout / 2),
+            None => self.status_interval,
+        };
+
+        timeout.max(Duration::from_millis(1))
+    }
+
+    // Fail if the server sent nothing for the receive timeout, asking it for a keepalive
+    // at half the timeout so an idle server gets a chance to answer
+    fn check_receive_timeout(&mut self) -> Result<(), Error> {
+        let Some(receive_timeout) = self.options.receive_timeout else {
+            return Ok(());
+        };
+        let silence = self.last_received.elapsed();
+
+        match self.reply_requested_at {
+            None if silence >= receive_timeout / 2 => {
+                self.send_standby_status_update(true)?;
+                self.last_status_update = Instant::now();
+                self.reply_requested_at = Some(Instant::now());
+            }
+            Some(requested_at)
+                if silence >= receive_timeout && requested_at.elapsed() >= receive_timeout / 2 =>
+            {
+                return Err(Error::ReplicationStreamTimedOut);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    // Send status updates at least twice per wal_sender_timeout, so the server does not
+    // end a connection that is still consumed
+    fn limit_status_interval(&mut self) -> Result<(), Error> {
+        let rows = self.connection.query("SHOW wal_sender_timeout")?;
+        let wal_sender_timeout = rows
+            .first()
+            .and_then(|row| row.first())
+            .and_then(Option::as_deref)
+            .and_then(parse_duration_setting);
+
+        // A timeout of 0 disables it
+        if let Some(timeout) = wal_sender_timeout
+            && !timeout.is_zero()
+        {
+            self.status_interval = self.status_interval.min(timeout / 2);
+        }
+
+        Ok(())
+    }
+
     // Start the replication process
     fn start_replication(&mut self) -> Result<(), Error> {
         // Send START_REPLICATION command
-        let start_replication_command = format!(
-            "START_REPLICATION SLOT {} LOGICAL 0/0 (proto_version '1', publication_names '{}')",
-            self.slot_name, self.publication_name
-        );
+        let start_replication_command =
+            start_replication_command(&self.options, self.connection.server_version_num())?;
 
         // Format as a Query message
         let mut query_data = Vec::new();
@@ -219,11 +575,20 @@ impl<T: Read + Write> Subscriber<T> {
                 }
                 b'E' => {
                     // ErrorResponse
-                    let error_message = parse_pg_error_message(&message.data)?;
-                    return Err(Error::ReplicationCommandFailed(error_message));
+                    return Err(DbError::parse(&message.data)?.into());
+                }
+                b'N' => {
+                    // NoticeResponse
+                    let notice = DbError::parse(&message.data)?;
+                    self.connection.emit(Event::Notice(Box::new(notice)));
+                }
+                b'S' => {
+                    // ParameterStatus, e.g. after a configuration reload
+                    self.connection.handle_parameter_status(&message.data)?;
                 }
                 _ => {
-                    eprintln!("Unexpected message type: {}", message.message_type as char);
+                    self.connection
+                        .emit(Event::UnknownMessage(message.message_type));
                 }
             }
         }
@@ -235,6 +600,81 @@ impl<T: Read + Write> Subscriber<T> {
         Ok(())
     }
 
+    /// Get the underlying replication connection.
+    ///
+    /// Server parameters reported while streaming are kept up to date on it.
+    pub fn connection(&self) -> &Connection<T> {
+        &self.connection
+    }
+
+    /// Set the handler for notices and other events of the replication connection.
+    pub fn set_event_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(Event) + Send + Sync + 'static,
+    {
+        self.connection.set_event_handler(handler);
+    }
+
+    /// Acknowledge that all changes up to a location have been durably processed.
+    ///
+    /// Only acknowledged locations are reported to the server as flushed and applied,
+    /// which lets it advance the slot and recycle the WAL before them. Call this once a
+    /// transaction has been persisted by the application, with the `end_lsn` of its commit.
+    /// The location is reported with the next status update.
+    ///
+    /// # Arguments
+    ///
+    /// * `lsn` - The location up to which changes are processed, earlier locations than
+    ///   already acknowledged are ignored
+    pub fn ack(&mut self, lsn: Lsn) {
+        if lsn > self.flushed_lsn {
+            self.flushed_lsn = lsn;
+        }
+    }
+
+    // Track a location received from the server, acknowledging it right away with auto_ack
+    fn receive(&mut self, lsn: Lsn) {
+        if lsn > self.received_lsn {
+            self.received_lsn = lsn;
+        }
+        if self.options.auto_ack {
+            self.ack(lsn);
+        }
+    }
+
+    /// Get the latest location received from the server, reported as written.
+    ///
+    /// This includes locations of keepalive messages and of messages not yet processed
+    /// by the application, so it must not be used to acknowledge changes.
+    pub fn received_lsn(&self) -> Lsn {
+        self.received_lsn
+    }
+
+    /// Get the latest location acknowledged with `ack`, reported as flushed and applied.
+    pub fn flushed_lsn(&self) -> Lsn {
+        self.flushed_lsn
+    }
+
+    /// Send a status update with the received and acknowledged locations right away.
+    ///
+    /// Status updates are also sent periodically while calling `next` and whenever the
+    /// server asks for one.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating success or an `Error` on failure.
+    pub fn send_status_update(&mut self) -> Result<(), Error> {
+        self.send_standby_status_update(self.options.request_reply)?;
+        self.last_status_update = Instant::now();
+        Ok(())
+    }
+
+    /// Get the interval between status updates, after limiting it to half the server's
+    /// `wal_sender_timeout`.
+    pub fn status_interval(&self) -> Duration {
+        self.status_interval
+    }
+
     /// Get information about a relation by its ID.
     ///
     /// Returns the cached relation information for the given relation ID,
@@ -251,6 +691,22 @@ impl<T: Read + Write> Subscriber<T> {
         self.relation_cache.get(&relation_id)
     }
 
+    /// Get information about a type that is not built in by its ID.
+    ///
+    /// Returns the cached type information for the given type ID, or `None` if
+    /// the server did not describe the type.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_id` - The OID of the type to look up, e.g. from `Column::type_id`
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Option` containing a reference to the `TypeInfo` if found.
+    pub fn type_info(&self, type_id: u32) -> Option<&TypeInfo> {
+        self.type_cache.get(&type_id)
+    }
+
     /// Get the next WAL message from the replication stream.
     ///
     /// This method blocks until a new WAL message is available and returns it.
@@ -260,26 +716,30 @@ impl<T: Read + Write> Subscriber<T> {
     /// # Returns
     ///
     /// Returns a `Result` containing the next `Message` on success, or an `Error` on failure.
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Result<Message, Error> {
         loop {
-            // Send standby status update every 10 seconds
-            if self.last_status_update.elapsed().as_secs() >= 10 {
-                self.send_standby_status_update()?;
-                self.last_status_update = std::time::Instant::now();
+            // Send standby status update at the configured interval
+            if self.last_status_update.elapsed() >= self.status_interval {
+                self.send_status_update()?;
             }
 
             // Read the next message - using copy_data=true since we're in replication mode
             let message = match self.connection.read_message(true) {
-                Ok(msg) => msg,
+                Ok(msg) => {
+                    self.last_received = Instant::now();
+                    self.reply_requested_at = None;
+                    msg
+                }
                 Err(e) => {
                     // Handle timeouts gracefully - need to adjust for our custom Error type
-                    if let Error::Io(io_err) = &e {
-                        if io_err.kind() == std::io::ErrorKind::WouldBlock
-                            || io_err.kind() == std::io::ErrorKind::TimedOut
-                        {
-                            // Just a timeout, try again
-                            continue;
-                        }
+                    if let Error::Io(io_err) = &e
+                        && (io_err.kind() == std::io::ErrorKind::WouldBlock
+                            || io_err.kind() == std::io::ErrorKind::TimedOut)
+                    {
+                        // Nothing arrived in time, check whether the server went silent
+                        self.check_receive_timeout()?;
+                        continue;
                     }
                     return Err(e); // Other error, propagate
                 }
@@ -290,19 +750,16 @@ impl<T: Read + Write> Subscriber<T> {
                     // Primary keepalive message
                     if message.data.len() >= 17 {
                         // 8 (LSN) + 8 (timestamp) + 1 (reply flag)
-                        let wal_end = read_u64_from_slice(&message.data[0..8])?;
+                        let wal_end = Lsn(read_u64_from_slice(&message.data[0..8])?);
 
                         // Update our LSN tracking
-                        if wal_end > self.last_received_lsn {
-                            self.last_received_lsn = wal_end;
-                        }
+                        self.receive(wal_end);
 
                         // Check if server wants a reply
                         let reply_required = message.data[16] != 0;
 
                         if reply_required {
-                            self.send_standby_status_update()?;
-                            self.last_status_update = std::time::Instant::now();
+                            self.send_status_update()?;
                         }
                     }
 
@@ -313,13 +770,11 @@ impl<T: Read + Write> Subscriber<T> {
                     if message.data.len() > 24 {
                         // Extract WAL start position and current end position
                         let _wal_start = read_u64_from_slice(&message.data[0..8])?;
-                        let wal_end = read_u64_from_slice(&message.data[8..16])?;
+                        let wal_end = Lsn(read_u64_from_slice(&message.data[8..16])?);
                         let _server_time = read_i64_from_slice(&message.data[16..24])?;
 
                         // Update our position tracking
-                        if wal_end > self.last_received_lsn {
-                            self.last_received_lsn = wal_end;
-                        }
+                        self.receive(wal_end);
 
                         // Parse the actual WAL message payload
                         let mut wal_data = &message.data[24..];
@@ -347,20 +802,46 @@ impl<T: Read + Write> Subscriber<T> {
                             );
                         }
 
+                        // Store type info in the cache if this is a Type message
+                        if let Message::Type {
+                            id,
+                            namespace,
+                            name,
+                        } = &wal_message
+                        {
+                            self.type_cache.insert(
+                                *id,
+                                TypeInfo {
+                                    namespace: namespace.clone(),
+                                    name: name.clone(),
+                                },
+                            );
+                        }
+
                         // Return the message to the caller
                         return Ok(wal_message);
                     }
 
                     continue;
                 }
+                b'S' => {
+                    // ParameterStatus, the server reports settings that changed while streaming
+                    self.connection.handle_parameter_status(&message.data)?;
+                    continue;
+                }
                 b'E' => {
-                    // Error message
-                    let error_message = parse_pg_error_message(&message.data)?;
-                    eprintln!("Error from server: {}", error_message);
-                    continue; // This was just an error message, continue to get a real message
+                    // Error message, the server ended the replication stream, e.g. after a cancel
+                    return Err(DbError::parse(&message.data)?.into());
+                }
+                b'N' => {
+                    // NoticeResponse, e.g. a warning raised while decoding
+                    let notice = DbError::parse(&message.data)?;
+                    self.connection.emit(Event::Notice(Box::new(notice)));
+                    continue;
                 }
                 _ => {
-                    eprintln!("Unhandled message type: {}", message.message_type as char);
+                    self.connection
+                        .emit(Event::UnknownMessage(message.message_type));
                     continue; // Unknown message, continue to get a real message
                 }
             }
@@ -368,7 +849,7 @@ impl<T: Read + Write> Subscriber<T> {
     }
 
     // Send standby status update to the server - this should be private since it's an implementation detail
-    fn send_standby_status_update(&mut self) -> Result<(), Error> {
+    fn send_standby_status_update(&mut self, request_reply: bool) -> Result<(), Error> {
         // Build the standby status message - Format according to PostgreSQL protocol:
         // - Int64 - write position (LSN)
         // - Int64 - flush position (LSN)
@@ -379,21 +860,21 @@ impl<T: Read + Write> Subscriber<T> {
         let mut message_data = Vec::new();
 
         // Current WAL position (LSN) that we've received and written
-        message_data.extend_from_slice(&self.last_received_lsn.to_be_bytes());
+        message_data.extend_from_slice(&self.received_lsn.0.to_be_bytes());
 
-        // Current WAL position (LSN) that we've flushed to disk - use same value for simplicity
-        message_data.extend_from_slice(&self.last_received_lsn.to_be_bytes());
+        // WAL position (LSN) the application acknowledged as durably processed, the server
+        // may only recycle WAL before this position
+        message_data.extend_from_slice(&self.flushed_lsn.0.to_be_bytes());
 
-        // Current WAL position (LSN) that we've applied - use same value for simplicity
-        message_data.extend_from_slice(&self.last_received_lsn.to_be_bytes());
+        // WAL position (LSN) that we've applied - the application applies what it acknowledges
+        message_data.extend_from_slice(&self.flushed_lsn.0.to_be_bytes());
 
-        // Current system clock time
-        let now = jiff::Zoned::now();
-        let pg_time = now.timestamp().as_microsecond() - 946684800000000; // Microseconds since 2000-01-01
+        // Current system clock time in microseconds since 2000-01-01
+        let pg_time = Timestamp::now().as_microsecond() - POSTGRES_EPOCH_MICROS;
         message_data.extend_from_slice(&pg_time.to_be_bytes());
 
-        // Reply requested flag (0 = no reply needed)
-        message_data.push(0);
+        // Reply requested flag, the server answers with a keepalive if set
+        message_data.push(request_reply as u8);
 
         // Send as CopyData message with type 'r' since we're in COPY mode during replication
         self.connection.write_message(b'r', &message_data, true)?;
@@ -414,13 +895,27 @@ impl<T: Read + Write> Subscriber<T> {
         match message_type {
             b'B' => {
                 // Begin message
-                let lsn = self.read_lsn(data)?;
-                Ok(Message::Begin(lsn))
+                let final_lsn = self.read_lsn(data)?;
+                let commit_time = self.read_timestamp(data)?;
+                let xid = self.read_u32(data)?;
+                Ok(Message::Begin {
+                    final_lsn,
+                    commit_time,
+                    xid,
+                })
             }
             b'C' => {
                 // Commit message
-                let lsn = self.read_lsn(data)?;
-                Ok(Message::Commit(lsn))
+                let flags = self.read_u8(data)?;
+                let commit_lsn = self.read_lsn(data)?;
+                let end_lsn = self.read_lsn(data)?;
+                let commit_time = self.read_timestamp(data)?;
+                Ok(Message::Commit {
+                    flags,
+                    commit_lsn,
+                    end_lsn,
+                    commit_time,
+                })
             }
             b'I' => {
                 // Insert message
@@ -486,6 +981,67 @@ impl<T: Read + Write> Subscriber<T> {
                     old_tuple_data,
                 })
             }
+            b'O' => {
+                // Origin message
+                let commit_lsn = self.read_lsn(data)?;
+                let name = self.read_string(data)?;
+
+                Ok(Message::Origin { commit_lsn, name })
+            }
+            b'M' => {
+                // Logical decoding message
+                let flags = self.read_u8(data)?;
+                let lsn = self.read_lsn(data)?;
+                let prefix = self.read_string(data)?;
+
+                let len = self.read_i32(data)?;
+                if len < 0 || data.len() < len as usize {
+                    return Err(Error::UnexpectedEndOfData("message content"));
+                }
+                let content = data[..len as usize].to_vec();
+                *data = &data[len as usize..];
+
+                Ok(Message::LogicalMessage {
+                    transactional: flags & MESSAGE_TRANSACTIONAL != 0,
+                    lsn,
+                    prefix,
+                    content,
+                })
+            }
+            b'Y' => {
+                // Type message
+                let id = self.read_u32(data)?;
+                let namespace = self.read_string(data)?;
+                let name = self.read_string(data)?;
+
+                Ok(Message::Type {
+                    id,
+                    namespace,
+                    name,
+                })
+            }
+            b'T' => {
+                // Truncate message
+                let relation_count = self.read_u32(data)?;
+
+                // Option bits: 1 for CASCADE, 2 for RESTART IDENTITY
+                let options = self.read_u8(data)?;
+
+                // Check the count against the data before trusting it for the allocation
+                if relation_count as usize > data.len() / 4 {
+                    return Err(Error::UnexpectedEndOfData("truncate relation ids"));
+                }
+                let mut relation_ids = Vec::with_capacity(relation_count as usize);
+                for _ in 0..relation_count {
+                    relation_ids.push(self.read_u32(data)?);
+                }
+
+                Ok(Message::Truncate {
+                    relation_ids,
+                    cascade: options & TRUNCATE_CASCADE != 0,
+                    restart_identity: options & TRUNCATE_RESTART_IDENTITY != 0,
+                })
+            }
             b'R' => {
                 // Relation message (table schema)
                 let id = self.read_u32(data)?;
@@ -534,10 +1090,7 @@ impl<T: Read + Write> Subscriber<T> {
                     columns,
                 })
             }
-            _ => {
-                eprintln!("Unknown message type: {}", message_type);
-                Ok(Message::Unknown(message_type))
-            }
+            _ => Ok(Message::Unknown(message_type)),
         }
     }
 
@@ -579,7 +1132,7 @@ impl<T: Read + Write> Subscriber<T> {
         Ok(value)
     }
 
-    fn read_lsn(&self, data: &mut &[u8]) -> Result<u64, Error> {
+    fn read_lsn(&self, data: &mut &[u8]) -> Result<Lsn, Error> {
         if data.len() < 8 {
             return Err(Error::UnexpectedEndOfData("LSN"));
         }
@@ -587,7 +1140,21 @@ impl<T: Read + Write> Subscriber<T> {
             data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
         ]);
         *data = &data[8..];
-        Ok(value)
+        Ok(Lsn(value))
+    }
+
+    fn read_timestamp(&self, data: &mut &[u8]) -> Result<Timestamp, Error> {
+        if data.len() < 8 {
+            return Err(Error::UnexpectedEndOfData("timestamp"));
+        }
+        let micros = read_i64_from_slice(data)?;
+        *data = &data[8..];
+
+        // Microseconds since the PostgreSQL epoch, which overflows for values near i64::MAX
+        let unix_micros = micros.checked_add(POSTGRES_EPOCH_MICROS).ok_or_else(|| {
+            Error::ReplicationProtocolViolation(format!("Timestamp {} out of range", micros))
+        })?;
+        Timestamp::from_microsecond(unix_micros).map_err(Error::ParseDateTime)
     }
 
     fn read_string(&self, data: &mut &[u8]) -> Result<String, Error> {
@@ -615,6 +1182,16 @@ impl<T: Read + Write> Subscriber<T> {
         Ok(result)
     }
 
+    // Get the type to decode a column with, the built-in base type for a domain
+    fn resolve_type_id(&self, type_id: u32) -> u32 {
+        match self.type_cache.get(&type_id) {
+            Some(type_info) if type_info.namespace.is_empty() => {
+                builtin_type_id(&type_info.name).unwrap_or(type_id)
+            }
+            _ => type_id,
+        }
+    }
+
     fn read_tuple_data(
         &self,
         data: &mut &[u8],
@@ -637,7 +1214,7 @@ impl<T: Read + Write> Subscriber<T> {
         // For each column in the tuple
         for i in 0..column_count {
             // Get column info from relation cache if available
-            let type_id = if let Some(rel) = relation_info {
+            let column_type_id = if let Some(rel) = relation_info {
                 if (i as usize) < rel.columns.len() {
                     let column = &rel.columns[i as usize];
                     column.type_id
@@ -649,6 +1226,7 @@ impl<T: Read + Write> Subscriber<T> {
                 // Fallback if relation not in cache
                 25
             };
+            let type_id = self.resolve_type_id(column_type_id);
 
             // Read the data format flag
             let format_byte = self.read_u8(data)?;
@@ -673,8 +1251,15 @@ impl<T: Read + Write> Subscriber<T> {
                         // Parse the text value directly from the slice
                         let text_str = std::str::from_utf8(text_slice)?;
 
-                        // Use the helper function to parse text value
-                        let value = parse_text_value(text_str, type_id)?;
+                        // Use the helper function to parse text value, keeping the text of
+                        // custom types without a parser, e.g. enums or citext
+                        let value = if !has_text_parser(type_id)
+                            && self.type_cache.contains_key(&column_type_id)
+                        {
+                            parse_text_value(text_str, PG_TYPE_TEXT)?
+                        } else {
+                            parse_text_value(text_str, type_id)?
+                        };
                         tuple_data.push(Some(value));
                     }
                 }
@@ -697,7 +1282,10 @@ impl<T: Read + Write> Subscriber<T> {
                         let value = match parse_binary_value(binary_data, type_id, len) {
                             Ok(v) => v,
                             Err(e) => {
-                                eprintln!("Warning: Failed to parse binary value: {}", e);
+                                self.connection.emit(Event::Warning(format!(
+                                    "Failed to parse binary value: {}",
+                                    e
+                                )));
                                 Value::Binary(binary_data.to_vec())
                             }
                         };
@@ -725,3 +1313,57 @@ impl<T: Read + Write> Subscriber<T> {
         Ok(tuple_data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_replication_command() {
+        let options = SubscriberOptions::new("my_slot", "my_publication");
+        assert_eq!(
+            start_replication_command(&options, Some(150000)).unwrap(),
+            "START_REPLICATION SLOT my_slot LOGICAL 0/0 (proto_version '1', publication_names 'my_publication')"
+        );
+
+        let options = options.start_lsn(Lsn(0x16B3748)).origin(OriginFilter::None);
+        assert_eq!(
+            start_replication_command(&options, Some(160000)).unwrap(),
+            "START_REPLICATION SLOT my_slot LOGICAL 0/16B3748 (proto_version '1', publication_names 'my_publication', origin 'none')"
+        );
+        assert!(matches!(
+            start_replication_command(&options, Some(150000)),
+            Err(Error::UnsupportedServerVersion(_))
+        ));
+
+        let options = SubscriberOptions::new("my_slot", "my_publication").messages(true);
+        assert_eq!(
+            start_replication_command(&options, Some(140000)).unwrap(),
+            "START_REPLICATION SLOT my_slot LOGICAL 0/0 (proto_version '1', publication_names 'my_publication', messages 'true')"
+        );
+        assert!(matches!(
+            start_replication_command(&options, Some(130000)),
+            Err(Error::UnsupportedServerVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_duration_setting() {
+        assert_eq!(
+            parse_duration_setting("1min"),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(parse_duration_setting("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(
+            parse_duration_setting("500ms"),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            parse_duration_setting("2h"),
+            Some(Duration::from_secs(7200))
+        );
+        assert_eq!(parse_duration_setting("0"), Some(Duration::ZERO));
+        assert_eq!(parse_duration_setting("-1"), None);
+        assert_eq!(parse_duration_setting("1 fortnight"), None);
+    }
+}
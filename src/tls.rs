@@ -0,0 +1,265 @@
+use crate::Error;
+use crate::config::ConnectOptions;
+use crate::socket::SetReadTimeout;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+// SSLRequest code: 1234 in the high 16 bits and 5679 in the low 16 bits
+const SSL_REQUEST_CODE: i32 = 80877103;
+
+/// Channel binding data of a TLS connection.
+///
+/// This is what the authentication layer needs from the transport to perform
+/// `SCRAM-SHA-256-PLUS`: the `tls-server-end-point` binding from RFC 5929, which is
+/// the hash of the server's certificate.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelBinding {
+    pub(crate) tls_server_end_point: Option<Vec<u8>>,
+}
+
+impl ChannelBinding {
+    /// No channel binding is available, e.g. for a connection without TLS.
+    pub fn none() -> Self {
+        ChannelBinding::default()
+    }
+
+    /// The `tls-server-end-point` channel binding.
+    ///
+    /// # Arguments
+    ///
+    /// * `certificate_hash` - The hash of the server's DER encoded certificate, computed
+    ///   with the certificate's signature hash algorithm (SHA-256 for MD5 and SHA-1)
+    pub fn tls_server_end_point(certificate_hash: Vec<u8>) -> Self {
+        ChannelBinding {
+            tls_server_end_point: Some(certificate_hash),
+        }
+    }
+}
+
+/// A stream encrypted with TLS that can expose channel binding data.
+///
+/// Implement this for the TLS stream type of whichever TLS library is used, so
+/// that `Connection` can bind the SCRAM authentication to the TLS session.
+pub trait TlsStream: Read + Write {
+    /// Get the channel binding data for this TLS session.
+    fn channel_binding(&self) -> ChannelBinding;
+}
+
+/// Channel binding policy, analogous to libpq's `channel_binding` option.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChannelBindingMode {
+    /// Never use channel binding.
+    Disable,
+    /// Use channel binding if the connection and the server support it.
+    #[default]
+    Prefer,
+    /// Fail the connection unless the server authenticates with channel binding.
+    Require,
+}
+
+/// TLS policy, analogous to libpq's `sslmode` option.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SslMode {
+    /// Never use TLS.
+    Disable,
+    /// Use TLS if the server supports it, without verifying the certificate.
+    ///
+    /// `ConnectOptions::connect` falls back to plain text when the server declines the
+    /// SSLRequest or the TLS handshake fails, opening a new connection in the latter case.
+    /// `Connection::with_tls` cannot reopen the caller's stream, so it only falls back
+    /// when the server declines and returns the handshake error otherwise.
+    #[default]
+    Prefer,
+    /// Always use TLS, without verifying the certificate.
+    Require,
+    /// Always use TLS and verify that the server certificate is signed by a trusted CA.
+    VerifyCa,
+    /// Always use TLS, verify the certificate chain and that the host name matches it.
+    VerifyFull,
+}
+
+impl SslMode {
+    // Whether the connection must fail rather than fall back to plain text
+    fn requires_tls(self) -> bool {
+        matches!(
+            self,
+            SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull
+        )
+    }
+}
+
+/// A TLS backend that can upgrade a stream after the server accepted an SSLRequest.
+///
+/// The crate ships an implementation based on `native-tls` behind the `native-tls`
+/// cargo feature, and `NoTls` for connections that never use TLS.
+pub trait TlsConnect<S: Read + Write> {
+    /// The TLS stream wrapping the underlying stream.
+    type Stream: TlsStream;
+
+    /// Whether this backend is able to establish TLS sessions at all.
+    ///
+    /// With `SslMode::Prefer`, no SSLRequest is sent if this returns `false`.
+    fn can_connect(&self) -> bool {
+        true
+    }
+
+    /// Perform the TLS handshake over `stream`.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The stream, positioned right after the server accepted the SSLRequest
+    /// * `domain` - The host name of the server, for SNI and certificate verification
+    /// * `mode` - The TLS policy, deciding how the server certificate is verified
+    fn connect(self, stream: S, domain: &str, mode: SslMode) -> Result<Self::Stream, Error>;
+}
+
+/// A TLS backend that never establishes TLS sessions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoTls;
+
+/// The stream type of `NoTls`, which can never be constructed.
+pub enum NoTlsStream {}
+
+impl<S: Read + Write> TlsConnect<S> for NoTls {
+    type Stream = NoTlsStream;
+
+    fn can_connect(&self) -> bool {
+        false
+    }
+
+    fn connect(self, _stream: S, _domain: &str, _mode: SslMode) -> Result<NoTlsStream, Error> {
+        Err(Error::Tls("no TLS backend configured".into()))
+    }
+}
+
+impl Read for NoTlsStream {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        match *self {}
+    }
+}
+
+impl Write for NoTlsStream {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        match *self {}
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {}
+    }
+}
+
+impl SetReadTimeout for NoTlsStream {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        match *self {}
+    }
+
+    fn set_read_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> {
+        match *self {}
+    }
+}
+
+impl TlsStream for NoTlsStream {
+    fn channel_binding(&self) -> ChannelBinding {
+        match *self {}
+    }
+}
+
+/// A stream that may or may not be encrypted with TLS.
+#[derive(Debug)]
+pub enum MaybeTlsStream<S, T> {
+    /// An unencrypted stream.
+    Raw(S),
+    /// A stream encrypted with TLS.
+    Tls(T),
+}
+
+impl<S: Read, T: Read> Read for MaybeTlsStream<S, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            MaybeTlsStream::Raw(stream) => stream.read(buf),
+            MaybeTlsStream::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl<S: Write, T: Write> Write for MaybeTlsStream<S, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            MaybeTlsStream::Raw(stream) => stream.write(buf),
+            MaybeTlsStream::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            MaybeTlsStream::Raw(stream) => stream.flush(),
+            MaybeTlsStream::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+impl<S: SetReadTimeout, T: SetReadTimeout> SetReadTimeout for MaybeTlsStream<S, T> {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        match self {
+            MaybeTlsStream::Raw(stream) => stream.read_timeout(),
+            MaybeTlsStream::Tls(stream) => stream.read_timeout(),
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            MaybeTlsStream::Raw(stream) => stream.set_read_timeout(timeout),
+            MaybeTlsStream::Tls(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+}
+
+impl<S: Read + Write, T: TlsStream> TlsStream for MaybeTlsStream<S, T> {
+    fn channel_binding(&self) -> ChannelBinding {
+        match self {
+            MaybeTlsStream::Raw(_) => ChannelBinding::none(),
+            MaybeTlsStream::Tls(stream) => stream.channel_binding(),
+        }
+    }
+}
+
+// Send an SSLRequest if the options call for it and upgrade the stream when the server accepts
+pub(crate) fn negotiate_tls<S, C>(
+    mut stream: S,
+    options: &ConnectOptions,
+    tls: C,
+) -> Result<MaybeTlsStream<S, C::Stream>, Error>
+where
+    S: Read + Write,
+    C: TlsConnect<S>,
+{
+    let mode = options.ssl_mode;
+
+    if mode == SslMode::Disable || (mode == SslMode::Prefer && !tls.can_connect()) {
+        return Ok(MaybeTlsStream::Raw(stream));
+    }
+
+    // SSLRequest: length (8) followed by the request code, no message type byte
+    let mut request = Vec::with_capacity(8);
+    request.extend_from_slice(&8i32.to_be_bytes());
+    request.extend_from_slice(&SSL_REQUEST_CODE.to_be_bytes());
+    stream.write_all(&request)?;
+
+    // The server answers with a single unframed byte. Reading exactly one byte
+    // makes sure nothing sent before the handshake is mistaken for encrypted data.
+    let mut response = [0u8; 1];
+    stream.read_exact(&mut response)?;
+
+    match response[0] {
+        b'S' => {
+            let stream = tls.connect(stream, options.first_host(), mode)?;
+            Ok(MaybeTlsStream::Tls(stream))
+        }
+        b'N' if mode.requires_tls() => Err(Error::TlsNotSupported),
+        b'N' => Ok(MaybeTlsStream::Raw(stream)),
+        other => Err(Error::ReplicationProtocolViolation(format!(
+            "Unexpected response to SSLRequest: {}",
+            other as char
+        ))),
+    }
+}
@@ -1,4 +1,5 @@
 use crate::Error;
+use crate::lsn::Lsn;
 use jiff::{Zoned, civil};
 
 // PostgreSQL type OIDs for common types
@@ -22,6 +23,7 @@ pub const PG_TYPE_TIMESTAMP: u32 = 1114;
 pub const PG_TYPE_TIMESTAMPTZ: u32 = 1184;
 pub const PG_TYPE_NUMERIC: u32 = 1700;
 pub const PG_TYPE_UUID: u32 = 2950;
+pub const PG_TYPE_PG_LSN: u32 = 3220;
 pub const PG_TYPE_JSONB: u32 = 3802;
 
 /// Represents various PostgreSQL data types and their values.
@@ -59,6 +61,8 @@ pub enum Value {
     Jsonb(String), // JSONB as string for simplicity
     /// Binary data (BYTEA).
     Binary(Vec<u8>),
+    /// Write-ahead log locations (PG_LSN).
+    Lsn(Lsn),
     /// NULL values.
     Null,
     /// Unknown or unsupported type with raw bytes and type OID.
@@ -82,6 +86,7 @@ impl std::fmt::Display for Value {
             Value::Json(j) => write!(f, "{}", j),
             Value::Jsonb(j) => write!(f, "{}", j),
             Value::Binary(b) => write!(f, "<binary data: {} bytes>", b.len()),
+            Value::Lsn(lsn) => write!(f, "{}", lsn),
             Value::Null => write!(f, "NULL"),
             Value::Unknown(_, oid) => write!(f, "<unknown type: {}>", oid),
         }
@@ -128,8 +133,8 @@ pub fn parse_text_value(text: &str, type_id: u32) -> Result<Value, Error> {
         }
         PG_TYPE_BYTEA => {
             // Handle bytea hex format \x followed by hex digits
-            if text.starts_with("\\x") {
-                match hex::decode(&text[2..]) {
+            if let Some(hex_digits) = text.strip_prefix("\\x") {
+                match hex::decode(hex_digits) {
                     Ok(bytes) => Ok(Value::Binary(bytes)),
                     Err(e) => Err(Error::HexDecode(e)),
                 }
@@ -167,10 +172,50 @@ pub fn parse_text_value(text: &str, type_id: u32) -> Result<Value, Error> {
             Ok(oid) => Ok(Value::Integer(oid as i32)),
             Err(e) => Err(Error::ParseInt(e)),
         },
+        PG_TYPE_PG_LSN => Ok(Value::Lsn(text.parse()?)),
         _ => Err(Error::ParseValue(format!("Unknown type_id: {}", type_id))),
     }
 }
 
+// Names and OIDs of the built-in types parse_text_value supports
+const TEXT_TYPES: &[(&str, u32)] = &[
+    ("bool", PG_TYPE_BOOL),
+    ("bytea", PG_TYPE_BYTEA),
+    ("char", PG_TYPE_CHAR),
+    ("name", PG_TYPE_NAME),
+    ("int8", PG_TYPE_INT8),
+    ("int2", PG_TYPE_INT2),
+    ("int4", PG_TYPE_INT4),
+    ("text", PG_TYPE_TEXT),
+    ("oid", PG_TYPE_OID),
+    ("json", PG_TYPE_JSON),
+    ("float4", PG_TYPE_FLOAT4),
+    ("float8", PG_TYPE_FLOAT8),
+    ("bpchar", PG_TYPE_BPCHAR),
+    ("varchar", PG_TYPE_VARCHAR),
+    ("date", PG_TYPE_DATE),
+    ("time", PG_TYPE_TIME),
+    ("timestamp", PG_TYPE_TIMESTAMP),
+    ("timestamptz", PG_TYPE_TIMESTAMPTZ),
+    ("numeric", PG_TYPE_NUMERIC),
+    ("uuid", PG_TYPE_UUID),
+    ("pg_lsn", PG_TYPE_PG_LSN),
+    ("jsonb", PG_TYPE_JSONB),
+];
+
+// Whether parse_text_value supports the type, values of other types can only be kept as text
+pub(crate) fn has_text_parser(type_id: u32) -> bool {
+    TEXT_TYPES.iter().any(|(_, oid)| *oid == type_id)
+}
+
+// Look up the OID of a built-in type with a parser by its name in pg_catalog
+pub(crate) fn builtin_type_id(name: &str) -> Option<u32> {
+    TEXT_TYPES
+        .iter()
+        .find(|(type_name, _)| *type_name == name)
+        .map(|(_, oid)| *oid)
+}
+
 // Helper function to parse binary value into appropriate Value based on type_id
 pub fn parse_binary_value(binary_data: &[u8], type_id: u32, len: i32) -> Result<Value, Error> {
     match type_id {
@@ -393,6 +438,20 @@ pub fn parse_binary_value(binary_data: &[u8], type_id: u32, len: i32) -> Result<
                 Err(_) => Ok(Value::Unknown(binary_data.to_vec(), type_id)),
             }
         }
+        PG_TYPE_PG_LSN if len == 8 => {
+            // PostgreSQL pg_lsn binary format: the location as a 64-bit integer
+            let value = u64::from_be_bytes([
+                binary_data[0],
+                binary_data[1],
+                binary_data[2],
+                binary_data[3],
+                binary_data[4],
+                binary_data[5],
+                binary_data[6],
+                binary_data[7],
+            ]);
+            Ok(Value::Lsn(Lsn(value)))
+        }
         PG_TYPE_NUMERIC => {
             // For NUMERIC, we just store the binary data since proper parsing is complex
             // A full implementation would require understanding the binary format
@@ -491,6 +550,7 @@ impl PartialEq for Value {
             (Value::Json(a), Value::Json(b)) => a == b,
             (Value::Jsonb(a), Value::Jsonb(b)) => a == b,
             (Value::Binary(a), Value::Binary(b)) => a == b,
+            (Value::Lsn(a), Value::Lsn(b)) => a == b,
             (Value::Null, Value::Null) => true,
             (Value::Unknown(a, oid_a), Value::Unknown(b, oid_b)) => a == b && oid_a == oid_b,
             _ => false,
@@ -576,7 +636,7 @@ mod tests {
                 dt.to_string().contains(expected_tz),
                 "Expected timezone {} in {}",
                 expected_tz,
-                dt.to_string()
+                dt
             );
         }
     }
@@ -632,4 +692,43 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_parse_pg_lsn() {
+        assert_eq!(
+            parse_text_value("0/16B3748", PG_TYPE_PG_LSN).unwrap(),
+            Value::Lsn(Lsn(0x16B3748))
+        );
+        assert!(parse_text_value("16B3748", PG_TYPE_PG_LSN).is_err());
+        assert_eq!(
+            parse_binary_value(&0x1_0000_00FFu64.to_be_bytes(), PG_TYPE_PG_LSN, 8).unwrap(),
+            Value::Lsn(Lsn(0x1_0000_00FF))
+        );
+        assert_eq!(Value::Lsn(Lsn(0x1_0000_00FF)).to_string(), "1/FF");
+    }
+
+    #[test]
+    fn test_text_types_match_parser() {
+        // A type has a text parser exactly if parse_text_value knows its OID
+        for type_id in 0..10000 {
+            let parsed = !matches!(
+                parse_text_value("", type_id),
+                Err(Error::ParseValue(message)) if message.starts_with("Unknown type_id")
+            );
+            assert_eq!(has_text_parser(type_id), parsed, "type_id {}", type_id);
+        }
+    }
+
+    #[test]
+    fn test_builtin_type_id() {
+        assert_eq!(builtin_type_id("int4"), Some(PG_TYPE_INT4));
+        assert_eq!(builtin_type_id("timestamptz"), Some(PG_TYPE_TIMESTAMPTZ));
+        assert_eq!(builtin_type_id("citext"), None);
+
+        // Every type with a text parser is found by its name
+        for (name, oid) in TEXT_TYPES {
+            assert_eq!(builtin_type_id(name), Some(*oid));
+            assert!(has_text_parser(*oid));
+        }
+    }
 }
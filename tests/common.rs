@@ -1,9 +1,15 @@
 use std::fs;
+use std::io::Read;
 use std::io::Write;
 use std::path::PathBuf;
 use std::process::Command;
 use std::process::Stdio;
 use std::str;
+use std::time::Duration;
+
+use lolrepl::Message;
+use lolrepl::SetReadTimeout;
+use lolrepl::Subscriber;
 
 // Fixed port for the test PostgreSQL server
 const PG_TEST_PORT: u16 = 23998;
@@ -16,7 +22,7 @@ pub struct TempDb {
 impl TempDb {
     pub fn execute(&self, s: &str) {
         let mut child = Command::new("psql")
-            .args(&[
+            .args([
                 "-p",
                 &self.port.to_string(),
                 "-U",
@@ -39,13 +45,42 @@ impl TempDb {
 
         let _ = child.wait().expect("Failed to wait on psql");
     }
+
+    // Add a pg_hba.conf rule ahead of the default trust rules and reload the server
+    #[allow(dead_code)]
+    pub fn prepend_hba_rule(&self, rule: &str) {
+        let hba_path = self.data_dir.join("pg_hba.conf");
+        let hba_content = fs::read_to_string(&hba_path).expect("Failed to read pg_hba.conf");
+        fs::write(&hba_path, format!("{}\n{}", rule, hba_content))
+            .expect("Failed to update pg_hba.conf");
+
+        self.reload();
+    }
+
+    // Append settings to postgresql.conf and reload the server
+    #[allow(dead_code)]
+    pub fn append_config(&self, settings: &str) {
+        let conf_path = self.data_dir.join("postgresql.conf");
+        let conf_content = fs::read_to_string(&conf_path).expect("Failed to read postgresql.conf");
+        fs::write(&conf_path, format!("{}\n{}\n", conf_content, settings))
+            .expect("Failed to update postgresql.conf");
+
+        self.reload();
+    }
+
+    fn reload(&self) {
+        self.execute("SELECT pg_reload_conf();");
+
+        // The reload is signalled asynchronously, give the postmaster a moment
+        std::thread::sleep(Duration::from_millis(500));
+    }
 }
 
 impl Drop for TempDb {
     fn drop(&mut self) {
         // Stop PostgreSQL server
         let output = Command::new("pg_ctl")
-            .args(&["-D", self.data_dir.to_str().unwrap(), "stop", "-m", "fast"])
+            .args(["-D", self.data_dir.to_str().unwrap(), "stop", "-m", "fast"])
             .stdout(Stdio::null())
             .stderr(Stdio::null())
             .output();
@@ -64,7 +99,7 @@ fn ensure_port_available() {
     if data_dir.exists() {
         // Try to stop PostgreSQL gracefully first with pg_ctl
         let pg_ctl_output = Command::new("pg_ctl")
-            .args(&["-D", data_dir.to_str().unwrap(), "stop", "-m", "fast"])
+            .args(["-D", data_dir.to_str().unwrap(), "stop", "-m", "fast"])
             .stdout(Stdio::null())
             .stderr(Stdio::null())
             .output();
@@ -80,6 +115,22 @@ fn ensure_port_available() {
     }
 }
 
+// Read the messages of the next transaction, up to and including its Commit
+#[allow(dead_code)]
+pub fn next_transaction<T: Read + Write + SetReadTimeout>(sub: &mut Subscriber<T>) -> Vec<Message> {
+    let mut messages = Vec::new();
+
+    loop {
+        let message = sub.next().expect("Failed to get replication message");
+        let is_commit = matches!(message, Message::Commit { .. });
+        messages.push(message);
+
+        if is_commit {
+            return messages;
+        }
+    }
+}
+
 pub fn init_tmp_db() -> TempDb {
     // Check for PostgreSQL installation
     let pg_version = Command::new("pg_config").arg("--version").output();
@@ -139,8 +190,7 @@ pub fn init_tmp_db() -> TempDb {
         fs::read_to_string(&postgresql_conf_path).expect("Failed to read postgresql.conf");
 
     // Add replication settings to the end of the file
-    let replication_settings = format!(
-        "
+    let replication_settings = "
 # Replication settings
 wal_level = logical
 max_wal_senders = 10
@@ -149,10 +199,9 @@ max_replication_slots = 10
 # Timezone settings
 timezone = 'Asia/Kolkata'
 log_timezone = 'Asia/Kolkata'
-"
-    );
+";
 
-    let new_conf = conf_content + &replication_settings;
+    let new_conf = conf_content + replication_settings;
     fs::write(&postgresql_conf_path, new_conf).expect("Failed to update postgresql.conf");
 
     // Start PostgreSQL server
@@ -185,7 +234,7 @@ log_timezone = 'Asia/Kolkata'
 
     // Check if PostgreSQL is actually running
     let pg_isready = Command::new("pg_isready")
-        .args(&["-p", &port.to_string()])
+        .args(["-p", &port.to_string()])
         .output();
 
     match pg_isready {
@@ -195,7 +244,7 @@ log_timezone = 'Asia/Kolkata'
 
     // Create testing database
     let createdb_output = Command::new("createdb")
-        .args(&["-p", &port.to_string(), "-U", "postgres", "testing"])
+        .args(["-p", &port.to_string(), "-U", "postgres", "testing"])
         .output()
         .expect("Failed to create database");
 
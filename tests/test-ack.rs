@@ -0,0 +1,119 @@
+mod common;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lolrepl::ConnectOptions;
+use lolrepl::Lsn;
+use lolrepl::Message;
+use lolrepl::NoTls;
+use lolrepl::Subscriber;
+use lolrepl::SubscriberOptions;
+use lolrepl::Value;
+
+// Get the confirmed flush location of the test slot over a separate connection
+fn confirmed_flush_lsn(options: &ConnectOptions) -> Lsn {
+    let mut connection = options.connect(NoTls).expect("Failed to connect");
+    let results = connection
+        .simple_query(
+            "SELECT confirmed_flush_lsn FROM pg_replication_slots WHERE slot_name = 'test_slot'",
+        )
+        .expect("Failed to query pg_replication_slots");
+
+    match results[0].rows[0][0] {
+        Value::Lsn(lsn) => lsn,
+        ref other => panic!("Expected a pg_lsn value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_ack() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+        INSERT INTO test_items (name) VALUES ('item1');
+    ",
+    );
+
+    let options = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing")
+        .read_timeout(Duration::from_secs(5));
+    let initial_lsn = confirmed_flush_lsn(&options);
+
+    let connection = options.connect(NoTls).expect("Failed to connect");
+    let mut sub = Subscriber::new(connection, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    let mut messages = Vec::new();
+    for _ in 0..4 {
+        messages.push(sub.next().expect("Failed to get replication message"));
+    }
+
+    let Message::Commit { end_lsn, .. } = messages[3] else {
+        panic!("Expected Commit message, got {:?}", messages[3]);
+    };
+    assert!(sub.received_lsn() >= end_lsn);
+    assert_eq!(sub.flushed_lsn(), Lsn::INVALID);
+
+    // Receiving changes does not confirm them
+    sub.send_status_update()
+        .expect("Failed to send status update");
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(confirmed_flush_lsn(&options), initial_lsn);
+
+    // Acknowledged locations are confirmed with the next status update
+    sub.ack(end_lsn);
+    sub.ack(initial_lsn);
+    assert_eq!(sub.flushed_lsn(), end_lsn);
+    sub.send_status_update()
+        .expect("Failed to send status update");
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while confirmed_flush_lsn(&options) != end_lsn {
+        assert!(
+            Instant::now() < deadline,
+            "Expected the slot to confirm {}",
+            end_lsn
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    // With auto_ack, every received location is confirmed
+    drop(sub);
+    temp_db.execute("INSERT INTO test_items (name) VALUES ('item2');");
+
+    let connection = options.connect(NoTls).expect("Failed to connect");
+    let mut sub = Subscriber::with_options(
+        connection,
+        SubscriberOptions::new("test_slot", "test_publication").auto_ack(true),
+    )
+    .expect("Failed to create subscriber");
+
+    let end_lsn = loop {
+        let message = sub.next().expect("Failed to get replication message");
+        if let Message::Commit { end_lsn, .. } = message {
+            break end_lsn;
+        }
+    };
+    assert!(sub.flushed_lsn() >= end_lsn);
+    sub.send_status_update()
+        .expect("Failed to send status update");
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while confirmed_flush_lsn(&options) < end_lsn {
+        assert!(
+            Instant::now() < deadline,
+            "Expected the slot to confirm {}",
+            end_lsn
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+}
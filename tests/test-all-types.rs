@@ -9,6 +9,7 @@ use lolrepl::Subscriber;
 use lolrepl::Value;
 
 #[test]
+#[allow(clippy::approx_constant)] // 3.14 and 2.718... are the literal values inserted above
 fn test_all_types() {
     // Setup a temporary database
     let temp_db = common::init_tmp_db();
@@ -94,7 +95,7 @@ fn test_all_types() {
         messages.push(message);
     }
 
-    let Message::Begin(_lsn) = messages[0] else {
+    let Message::Begin { .. } = messages[0] else {
         panic!("Expected Begin message");
     };
 
@@ -216,5 +217,5 @@ fn test_all_types() {
     ));
 
     // Verify Commit message
-    assert!(matches!(messages[3], Message::Commit(_)));
+    assert!(matches!(messages[3], Message::Commit { .. }));
 }
@@ -0,0 +1,95 @@
+mod common;
+
+use std::thread;
+use std::time::Duration;
+
+use lolrepl::ConnectOptions;
+use lolrepl::Error;
+use lolrepl::NoTls;
+use lolrepl::Subscriber;
+
+fn connect_options(port: u16) -> ConnectOptions {
+    ConnectOptions::new()
+        .host("localhost")
+        .port(port)
+        .user("postgres")
+        .database("testing")
+        .read_timeout(Duration::from_secs(5))
+}
+
+// Read from the stream until the server ends it, failing after a bounded number of messages
+fn expect_stream_error<T: std::io::Read + std::io::Write + lolrepl::SetReadTimeout>(
+    sub: &mut Subscriber<T>,
+) -> Error {
+    for _ in 0..100 {
+        if let Err(err) = sub.next() {
+            return err;
+        }
+    }
+
+    panic!("Expected the replication stream to fail");
+}
+
+#[test]
+fn test_cancel() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+    ",
+    );
+
+    // Cancel a running START_REPLICATION from another thread
+    let connection = connect_options(temp_db.port)
+        .connect(NoTls)
+        .expect("Failed to connect");
+    let cancel_token = connection.cancel_token();
+    assert_eq!(cancel_token.process_id(), connection.process_id());
+
+    let mut sub = Subscriber::new(connection, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(500));
+        cancel_token.cancel()
+    });
+
+    let err = expect_stream_error(&mut sub);
+    assert_eq!(
+        err.code(),
+        Some("57014"),
+        "Expected canceled replication command, got {:?}",
+        err
+    );
+    canceller
+        .join()
+        .expect("Cancel thread panicked")
+        .expect("Failed to send cancel request");
+    drop(sub);
+
+    // Terminate the walsender through a separate connection
+    let connection = connect_options(temp_db.port)
+        .connect(NoTls)
+        .expect("Failed to connect");
+    let cancel_token = connection.cancel_token();
+
+    let mut sub = Subscriber::new(connection, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    let terminated = cancel_token
+        .terminate(NoTls)
+        .expect("Failed to terminate backend");
+    assert!(terminated, "Expected the walsender to be signalled");
+
+    let err = expect_stream_error(&mut sub);
+    assert_eq!(
+        err.code(),
+        Some("57P01"),
+        "Expected terminated walsender, got {:?}",
+        err
+    );
+}
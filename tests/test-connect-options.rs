@@ -0,0 +1,105 @@
+mod common;
+
+use std::time::Duration;
+
+use lolrepl::ConnectOptions;
+use lolrepl::Error;
+use lolrepl::Message;
+use lolrepl::NoTls;
+use lolrepl::Subscriber;
+
+#[test]
+fn test_connect_options() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+        INSERT INTO test_items (name) VALUES ('item1');
+    ",
+    );
+
+    // Connect over TCP from a URI
+    let options: ConnectOptions = format!(
+        "postgresql://postgres@localhost:{}/testing?application_name=lolrepl_test&sslmode=disable&connect_timeout=5",
+        temp_db.port
+    )
+    .parse()
+    .expect("Failed to parse connection URI");
+    let connection = options
+        .read_timeout(Duration::from_secs(5))
+        .connect(NoTls)
+        .expect("Failed to connect over TCP");
+
+    // Startup reports server parameters and the backend key
+    assert!(connection.parameter("server_version").is_some());
+    assert_eq!(
+        connection.parameter("application_name"),
+        Some("lolrepl_test")
+    );
+    assert!(
+        connection
+            .parameter("DateStyle")
+            .is_some_and(|date_style| date_style.starts_with("ISO"))
+    );
+    assert!(connection.process_id() > 0);
+
+    let mut sub = Subscriber::new(connection, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+    assert!(
+        matches!(
+            sub.next().expect("Failed to read message"),
+            Message::Begin { .. }
+        ),
+        "Expected Begin message"
+    );
+    drop(sub);
+
+    // Connect over the Unix domain socket from a key=value string
+    let options = ConnectOptions::parse(&format!(
+        "host=/var/run/postgresql port={} user=postgres dbname=testing",
+        temp_db.port
+    ))
+    .expect("Failed to parse connection string");
+    options
+        .connect(NoTls)
+        .expect("Failed to connect over Unix socket");
+
+    // The client asks for ISO dates, even if the role is configured otherwise
+    temp_db.execute("ALTER ROLE postgres SET datestyle = 'SQL, DMY';");
+    let connection = options
+        .clone()
+        .options("-c statement_timeout=0")
+        .param("TimeZone", "UTC")
+        .connect(NoTls)
+        .expect("Failed to connect with startup parameters");
+    assert!(
+        connection
+            .parameter("DateStyle")
+            .is_some_and(|date_style| date_style.starts_with("ISO"))
+    );
+    assert_eq!(connection.parameter("TimeZone"), Some("UTC"));
+    assert_eq!(connection.parameter("client_encoding"), Some("UTF8"));
+
+    // Settings the value parsers cannot handle are rejected up front
+    let result = options
+        .clone()
+        .param("DateStyle", "SQL, DMY")
+        .connect(NoTls);
+    assert!(
+        matches!(result, Err(Error::UnsupportedServerSetting(_))),
+        "Expected unsupported DateStyle"
+    );
+    temp_db.execute("ALTER ROLE postgres RESET datestyle;");
+
+    // Nothing listens on the port after the server
+    let result = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port + 1)
+        .user("postgres")
+        .connect(NoTls);
+    assert!(matches!(result, Err(Error::Io(_))), "Expected IO error");
+}
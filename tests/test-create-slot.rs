@@ -0,0 +1,92 @@
+mod common;
+
+use std::time::Duration;
+
+use lolrepl::ConnectOptions;
+use lolrepl::CreateSlotOptions;
+use lolrepl::Error;
+use lolrepl::Lsn;
+use lolrepl::Message;
+use lolrepl::NoTls;
+use lolrepl::SnapshotAction;
+use lolrepl::Subscriber;
+
+#[test]
+fn test_create_slot() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+    ",
+    );
+
+    let options = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing")
+        .read_timeout(Duration::from_secs(5));
+
+    // A permanent slot with an exported snapshot
+    let mut connection = options.connect(NoTls).expect("Failed to connect");
+    let slot = connection
+        .create_replication_slot(
+            "exported_slot",
+            &CreateSlotOptions::new().snapshot(SnapshotAction::Export),
+        )
+        .expect("Failed to create slot");
+    assert_eq!(slot.slot_name, "exported_slot");
+    assert_eq!(slot.output_plugin.as_deref(), Some("pgoutput"));
+    assert!(
+        slot.snapshot_name.is_some(),
+        "Expected an exported snapshot"
+    );
+    assert_ne!(slot.consistent_point, Lsn::INVALID);
+
+    // Creating it again fails with duplicate_object
+    let err = connection
+        .create_replication_slot("exported_slot", &CreateSlotOptions::new())
+        .expect_err("Expected duplicate slot to fail");
+    assert_eq!(err.code(), Some("42710"), "Unexpected error {:?}", err);
+
+    // Failover slots are rejected before they reach older servers
+    if connection.server_version_num().unwrap() < 170000 {
+        let result = connection
+            .create_replication_slot("failover_slot", &CreateSlotOptions::new().failover(true));
+        assert!(
+            matches!(result, Err(Error::UnsupportedServerVersion(_))),
+            "Expected unsupported failover slot"
+        );
+    }
+
+    // A temporary slot streams changes made after its consistent point on the same connection
+    let mut connection = options.connect(NoTls).expect("Failed to connect");
+    let slot = connection
+        .create_replication_slot(
+            "temporary_slot",
+            &CreateSlotOptions::new()
+                .temporary(true)
+                .two_phase(true)
+                .snapshot(SnapshotAction::Nothing),
+        )
+        .expect("Failed to create temporary slot");
+    assert_eq!(slot.snapshot_name, None);
+
+    temp_db.execute("INSERT INTO test_items (name) VALUES ('item1');");
+
+    let mut sub = Subscriber::new(connection, "temporary_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    let mut messages = Vec::new();
+    for _ in 0..4 {
+        messages.push(sub.next().expect("Failed to get replication message"));
+    }
+
+    assert!(matches!(messages[0], Message::Begin { .. }));
+    assert!(matches!(messages[1], Message::Relation { .. }));
+    assert!(matches!(messages[2], Message::Insert { .. }));
+    assert!(matches!(messages[3], Message::Commit { .. }));
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use std::net::TcpStream;
+use std::time::Duration;
+
+use lolrepl::Connection;
+use lolrepl::Message;
+use lolrepl::Subscriber;
+use lolrepl::TypeInfo;
+use lolrepl::Value;
+
+#[test]
+fn test_custom_types() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE EXTENSION citext;
+        CREATE EXTENSION hstore;
+
+        CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy');
+        CREATE DOMAIN positive_int AS INTEGER CHECK (VALUE > 0);
+
+        CREATE TABLE test_custom (
+            id SERIAL PRIMARY KEY,
+            mood mood,
+            quantity positive_int,
+            email citext,
+            attributes hstore
+        );
+
+        CREATE PUBLICATION test_publication FOR TABLE test_custom;
+
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+    ",
+    );
+
+    temp_db.execute(
+        "
+        INSERT INTO test_custom (mood, quantity, email, attributes)
+        VALUES ('happy', 42, 'Someone@Example.com', 'color => blue');
+    ",
+    );
+
+    let stream = TcpStream::connect(format!("localhost:{}", temp_db.port))
+        .expect("Failed to connect to PostgreSQL");
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("Failed to set read timeout");
+
+    let conn = Connection::new(stream, "postgres", "", "testing")
+        .expect("Failed to create replication connection");
+
+    let mut sub = Subscriber::new(conn, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    let mut types = Vec::new();
+    let tuple_data = loop {
+        match sub.next().expect("Failed to get replication message") {
+            Message::Type { id, name, .. } => types.push((id, name)),
+            Message::Insert { tuple_data, .. } => break tuple_data,
+            _ => {}
+        }
+    };
+
+    // Each custom type is described before the relation that uses it
+    let mut names: Vec<_> = types.iter().map(|(_, name)| name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["citext", "hstore", "int4", "mood"]);
+
+    // A domain is described by its base type
+    let (domain_id, _) = types.iter().find(|(_, name)| name == "int4").unwrap();
+    assert_eq!(
+        sub.type_info(*domain_id),
+        Some(&TypeInfo {
+            namespace: String::new(),
+            name: "int4".to_string(),
+        })
+    );
+
+    let (enum_id, _) = types.iter().find(|(_, name)| name == "mood").unwrap();
+    assert_eq!(sub.type_info(*enum_id).unwrap().namespace, "public");
+
+    assert_eq!(
+        tuple_data,
+        vec![
+            Some(Value::Integer(1)),
+            Some(Value::Text("happy".to_string())),
+            Some(Value::Integer(42)),
+            Some(Value::Text("Someone@Example.com".to_string())),
+            Some(Value::Text("\"color\"=>\"blue\"".to_string())),
+        ]
+    );
+}
@@ -0,0 +1,99 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+use lolrepl::ConnectOptions;
+use lolrepl::Error;
+use lolrepl::NoTls;
+
+#[test]
+fn test_env_config() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    // Create a user that has to authenticate with a password
+    temp_db.execute(
+        "
+        SET password_encryption = 'scram-sha-256';
+        CREATE USER env_user WITH REPLICATION LOGIN PASSWORD 'env:secret';
+    ",
+    );
+    temp_db.prepend_hba_rule("host all env_user all scram-sha-256");
+
+    let dir = std::env::temp_dir().join(format!("lolrepl-env-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Failed to create config directory");
+
+    let service_file = dir.join("pg_service.conf");
+    fs::write(
+        &service_file,
+        format!(
+            "[replica]\nhost=localhost\nport={}\nuser=env_user\ndbname=testing\n",
+            temp_db.port
+        ),
+    )
+    .expect("Failed to write service file");
+
+    let passfile = dir.join("pgpass");
+    fs::write(
+        &passfile,
+        format!(
+            "# test passwords\n127.0.0.1:{}:*:env_user:wrong\nlocalhost:{}:*:env_user:env\\:secret\n",
+            temp_db.port, temp_db.port
+        ),
+    )
+    .expect("Failed to write password file");
+    fs::set_permissions(&passfile, fs::Permissions::from_mode(0o600))
+        .expect("Failed to set password file permissions");
+
+    // The test binary only runs this test, nothing else reads the environment concurrently
+    unsafe {
+        std::env::set_var("PGSERVICEFILE", &service_file);
+        std::env::set_var("PGPASSFILE", &passfile);
+        std::env::set_var("PGSERVICE", "replica");
+        std::env::set_var("PGSSLMODE", "disable");
+        std::env::remove_var("PGPASSWORD");
+    }
+
+    // The service and the password file provide everything needed to connect
+    ConnectOptions::from_env()
+        .expect("Failed to resolve options from environment")
+        .connect(NoTls)
+        .expect("Failed to connect with resolved options");
+
+    // Services can also be referenced from a connection string
+    ConnectOptions::parse("service=replica sslmode=disable")
+        .and_then(ConnectOptions::lookup_password)
+        .expect("Failed to resolve service")
+        .connect(NoTls)
+        .expect("Failed to connect with service");
+
+    // Each host uses its own password, the first one is rejected
+    ConnectOptions::parse(&format!(
+        "host=127.0.0.1,localhost port={} user=env_user dbname=testing sslmode=disable",
+        temp_db.port
+    ))
+    .expect("Failed to parse connection string")
+    .connect(NoTls)
+    .expect("Failed to connect to the second host");
+
+    // A password file readable by others is ignored
+    fs::set_permissions(&passfile, fs::Permissions::from_mode(0o644))
+        .expect("Failed to set password file permissions");
+    let options = ConnectOptions::from_env().expect("Failed to resolve options from environment");
+    assert!(
+        matches!(options.connect(NoTls), Err(Error::Authentication(_))),
+        "Expected missing password"
+    );
+
+    // Unknown services are an error
+    let result = ConnectOptions::parse("service=missing");
+    assert!(
+        matches!(result, Err(Error::ServiceNotFound(_))),
+        "Expected unknown service"
+    );
+
+    fs::remove_dir_all(&dir).expect("Failed to remove config directory");
+}
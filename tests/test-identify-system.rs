@@ -0,0 +1,44 @@
+mod common;
+
+use lolrepl::ConnectOptions;
+use lolrepl::NoTls;
+
+#[test]
+fn test_identify_system() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    let mut connection = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing")
+        .connect(NoTls)
+        .expect("Failed to connect");
+
+    let identity = connection
+        .identify_system()
+        .expect("Failed to identify system");
+    assert_ne!(identity.system_id, 0);
+    assert_eq!(identity.timeline, 1);
+    assert_eq!(identity.dbname.as_deref(), Some("testing"));
+
+    // Writing WAL moves the flush location forward, the identity stays the same
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        INSERT INTO test_items (name) VALUES ('first');
+    ",
+    );
+
+    let later = connection
+        .identify_system()
+        .expect("Failed to identify system again");
+    assert_eq!(later.system_id, identity.system_id);
+    assert!(
+        later.xlogpos > identity.xlogpos,
+        "Expected xlogpos {} to advance past {}",
+        later.xlogpos,
+        identity.xlogpos
+    );
+}
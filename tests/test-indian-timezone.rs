@@ -68,7 +68,7 @@ fn test_indian_timezone_replication() {
         messages.push(message);
     }
 
-    let Message::Begin(_lsn) = messages[0] else {
+    let Message::Begin { .. } = messages[0] else {
         panic!("Expected Begin message");
     };
 
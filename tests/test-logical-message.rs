@@ -0,0 +1,101 @@
+mod common;
+
+use std::time::Duration;
+
+use lolrepl::ConnectOptions;
+use lolrepl::Message;
+use lolrepl::NoTls;
+use lolrepl::Subscriber;
+use lolrepl::SubscriberOptions;
+
+#[test]
+fn test_logical_message() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+    ",
+    );
+
+    // A non-transactional marker, then an outbox event written with a row
+    temp_db.execute(
+        "
+        SELECT pg_logical_emit_message(false, 'marker', 'checkpoint');
+
+        BEGIN;
+        INSERT INTO test_items (name) VALUES ('item1');
+        SELECT pg_logical_emit_message(true, 'outbox', '{\"event\": \"created\"}');
+        COMMIT;
+    ",
+    );
+
+    let options = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing")
+        .read_timeout(Duration::from_secs(5));
+
+    // Messages are not streamed unless requested
+    let connection = options.connect(NoTls).expect("Failed to connect");
+    let mut sub = Subscriber::new(connection, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    let messages = common::next_transaction(&mut sub);
+    assert!(
+        !messages
+            .iter()
+            .any(|message| matches!(message, Message::LogicalMessage { .. })),
+        "Expected no logical messages, got {:?}",
+        messages
+    );
+    drop(sub);
+
+    // Without an acknowledgement, the slot streams the same changes again
+    let connection = options.connect(NoTls).expect("Failed to connect");
+    let mut sub = Subscriber::with_options(
+        connection,
+        SubscriberOptions::new("test_slot", "test_publication").messages(true),
+    )
+    .expect("Failed to create subscriber");
+
+    // The non-transactional message arrives on its own
+    let marker = sub.next().expect("Failed to get replication message");
+    let Message::LogicalMessage {
+        transactional,
+        lsn: marker_lsn,
+        prefix,
+        content,
+    } = marker
+    else {
+        panic!("Expected a logical message, got {:?}", marker);
+    };
+    assert!(!transactional);
+    assert_eq!(prefix, "marker");
+    assert_eq!(content, b"checkpoint");
+
+    // The transactional message is part of the transaction, after the row
+    let messages = common::next_transaction(&mut sub);
+    assert!(matches!(messages[0], Message::Begin { .. }));
+    assert!(matches!(messages[2], Message::Insert { .. }));
+
+    let Message::LogicalMessage {
+        transactional,
+        lsn,
+        ref prefix,
+        ref content,
+    } = messages[3]
+    else {
+        panic!("Expected a logical message, got {:?}", messages[3]);
+    };
+    assert!(transactional);
+    assert!(lsn > marker_lsn);
+    assert_eq!(prefix, "outbox");
+    assert_eq!(content, br#"{"event": "created"}"#);
+    assert!(matches!(messages[4], Message::Commit { .. }));
+}
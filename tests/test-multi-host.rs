@@ -0,0 +1,52 @@
+mod common;
+
+use std::time::Duration;
+
+use lolrepl::ConnectOptions;
+use lolrepl::Error;
+use lolrepl::NoTls;
+use lolrepl::TargetSessionAttrs;
+
+#[test]
+fn test_multi_host() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    // Nothing listens on the first port, the second host is the test server
+    let options: ConnectOptions = format!(
+        "postgresql://postgres@localhost:{},localhost:{}/testing?sslmode=disable",
+        temp_db.port + 1,
+        temp_db.port
+    )
+    .parse()
+    .expect("Failed to parse connection URI");
+    let options = options.connect_timeout(Duration::from_secs(5));
+
+    let connection = options.connect(NoTls).expect("Failed to fail over");
+    assert!(connection.process_id() > 0);
+
+    // The test server is a primary
+    for target_session_attrs in [
+        TargetSessionAttrs::ReadWrite,
+        TargetSessionAttrs::Primary,
+        TargetSessionAttrs::PreferStandby,
+    ] {
+        options
+            .clone()
+            .target_session_attrs(target_session_attrs)
+            .connect(NoTls)
+            .unwrap_or_else(|err| panic!("Expected {:?} to match: {}", target_session_attrs, err));
+    }
+
+    for target_session_attrs in [TargetSessionAttrs::ReadOnly, TargetSessionAttrs::Standby] {
+        let result = options
+            .clone()
+            .target_session_attrs(target_session_attrs)
+            .connect(NoTls);
+        assert!(
+            matches!(result, Err(Error::NoSuitableServer(_))),
+            "Expected {:?} not to match",
+            target_session_attrs
+        );
+    }
+}
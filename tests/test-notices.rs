@@ -0,0 +1,62 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use lolrepl::ConnectOptions;
+use lolrepl::Event;
+use lolrepl::Message;
+use lolrepl::NoTls;
+use lolrepl::Subscriber;
+
+#[test]
+fn test_notices() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+        INSERT INTO test_items (name) VALUES ('item1');
+    ",
+    );
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let handler_events = events.clone();
+
+    // Have the walsender send its LOG messages to the client
+    let options = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing")
+        .options("-c client_min_messages=log")
+        .event_handler(move |event| handler_events.lock().unwrap().push(event));
+
+    let connection = options.connect(NoTls).expect("Failed to connect");
+    let mut sub = Subscriber::new(connection, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    let message = sub.next().expect("Failed to get replication message");
+    assert!(matches!(message, Message::Begin { .. }));
+
+    // Messages of the decoding session arrive as notices, not as unknown messages
+    let events = events.lock().unwrap();
+    assert!(
+        events.iter().any(|event| matches!(
+            event,
+            Event::Notice(notice)
+                if notice.severity == "LOG" && notice.message.contains("logical decoding")
+        )),
+        "Expected a notice from logical decoding, got {:?}",
+        events
+    );
+    assert!(
+        !events
+            .iter()
+            .any(|event| matches!(event, Event::UnknownMessage(_))),
+        "Unexpected unknown message in {:?}",
+        events
+    );
+}
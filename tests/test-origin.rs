@@ -0,0 +1,102 @@
+mod common;
+
+use std::time::Duration;
+
+use lolrepl::ConnectOptions;
+use lolrepl::Error;
+use lolrepl::Lsn;
+use lolrepl::Message;
+use lolrepl::NoTls;
+use lolrepl::OriginFilter;
+use lolrepl::Subscriber;
+use lolrepl::SubscriberOptions;
+use lolrepl::Value;
+
+#[test]
+fn test_origin() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+        SELECT pg_replication_origin_create('node_b');
+    ",
+    );
+
+    // A transaction replayed from another node, followed by a local one
+    temp_db.execute(
+        "
+        SELECT pg_replication_origin_session_setup('node_b');
+        BEGIN;
+        SELECT pg_replication_origin_xact_setup('0/ABCDEF', now());
+        INSERT INTO test_items (name) VALUES ('remote');
+        COMMIT;
+        SELECT pg_replication_origin_session_reset();
+
+        INSERT INTO test_items (name) VALUES ('local');
+    ",
+    );
+
+    let options = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing")
+        .read_timeout(Duration::from_secs(5));
+
+    // By default changes of any origin are streamed
+    let connection = options.connect(NoTls).expect("Failed to connect");
+    let server_version = connection.server_version_num().unwrap();
+    let mut sub = Subscriber::new(connection, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    let remote = common::next_transaction(&mut sub);
+    assert!(matches!(remote[0], Message::Begin { .. }));
+    assert_eq!(
+        remote[1],
+        Message::Origin {
+            commit_lsn: Lsn(0xABCDEF),
+            name: "node_b".to_string(),
+        }
+    );
+
+    let local = common::next_transaction(&mut sub);
+    assert!(
+        !local
+            .iter()
+            .any(|message| matches!(message, Message::Origin { .. })),
+        "Expected no Origin message for a local transaction, got {:?}",
+        local
+    );
+    drop(sub);
+
+    // Filtering local changes needs PostgreSQL 16
+    let connection = options.connect(NoTls).expect("Failed to connect");
+    let result = Subscriber::with_options(
+        connection,
+        SubscriberOptions::new("test_slot", "test_publication").origin(OriginFilter::None),
+    );
+
+    if server_version < 160000 {
+        assert!(
+            matches!(result, Err(Error::UnsupportedServerVersion(_))),
+            "Expected the origin option to be rejected"
+        );
+        return;
+    }
+
+    let mut sub = result.expect("Failed to create subscriber");
+    let local = common::next_transaction(&mut sub);
+    let names: Vec<_> = local
+        .iter()
+        .filter_map(|message| match message {
+            Message::Insert { tuple_data, .. } => Some(tuple_data[1].clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec![Some(Value::Text("local".to_string()))]);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use lolrepl::ConnectOptions;
+use lolrepl::NoTls;
+use lolrepl::ProtocolVersion;
+
+#[test]
+fn test_protocol_version() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    let options = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing");
+
+    // Protocol 3.0 is requested by default and needs no negotiation
+    let connection = options.clone().connect(NoTls).expect("Failed to connect");
+    assert_eq!(connection.protocol_version(), ProtocolVersion::V3_0);
+    assert_eq!(connection.secret_key().len(), 4);
+
+    // Servers before PostgreSQL 18 negotiate a request for 3.2 down to 3.0
+    let connection = options
+        .max_protocol_version(ProtocolVersion::V3_2)
+        .connect(NoTls)
+        .expect("Failed to connect requesting protocol 3.2");
+    let server_major: u32 = connection
+        .parameter("server_version")
+        .and_then(|version| version.split(['.', ' ']).next()?.parse().ok())
+        .expect("Expected server_version");
+
+    let expected = if server_major >= 18 {
+        ProtocolVersion::V3_2
+    } else {
+        ProtocolVersion::V3_0
+    };
+    assert_eq!(connection.protocol_version(), expected);
+    assert!(connection.rejected_protocol_options().is_empty());
+
+    // The cancel token carries the key data of whichever version was negotiated
+    let cancel_token = connection.cancel_token();
+    cancel_token
+        .cancel()
+        .expect("Failed to send cancel request");
+}
@@ -57,15 +57,33 @@ fn test_replication() {
 
     let mut messages = Vec::new();
 
-    // We need to read exactly 4 more messages (Relation, Insert, Insert, Commit)
-    for _ in 0..4 {
+    // We need to read exactly 5 messages (Begin, Relation, Insert, Insert, Commit)
+    for _ in 0..5 {
         let message = sub.next().expect("Failed to get replication message");
         messages.push(message);
     }
 
-    let Message::Begin(lsn) = messages[0] else {
+    let Message::Begin {
+        final_lsn,
+        commit_time,
+        xid,
+    } = messages[0]
+    else {
         panic!("Expected Begin message");
     };
+    let Message::Commit { end_lsn, .. } = messages[4] else {
+        panic!("Expected Commit message");
+    };
+
+    // The transaction committed moments ago, and ends after its commit record
+    let age = jiff::Timestamp::now().duration_since(commit_time);
+    assert!(
+        age.as_secs() < 60,
+        "Expected a recent commit time, got {}",
+        commit_time
+    );
+    assert!(end_lsn > final_lsn);
+    assert_ne!(xid, 0);
     let Message::Relation {
         id: relation_id, ..
     } = messages[1]
@@ -78,11 +96,15 @@ fn test_replication() {
     // 2. Relation - describing the table
     // 3. Insert - first row
     // 4. Insert - second row
-    // 5. Commit - end of transaction
+    // 5. Commit - end of transaction, at the location and time announced by Begin
     assert_eq!(
         messages,
         vec![
-            Message::Begin(lsn),
+            Message::Begin {
+                final_lsn,
+                commit_time,
+                xid,
+            },
             Message::Relation {
                 id: relation_id,
                 namespace: "public".to_string(),
@@ -125,6 +147,12 @@ fn test_replication() {
                     Some(Value::Integer(200)),
                 ],
             },
+            Message::Commit {
+                flags: 0,
+                commit_lsn: final_lsn,
+                end_lsn,
+                commit_time,
+            },
         ]
     );
 }
@@ -0,0 +1,76 @@
+mod common;
+
+use std::net::TcpStream;
+use std::time::Duration;
+
+use lolrepl::ChannelBindingMode;
+use lolrepl::ConnectOptions;
+use lolrepl::Connection;
+use lolrepl::Error;
+
+fn open_stream(port: u16) -> TcpStream {
+    let stream =
+        TcpStream::connect(format!("localhost:{}", port)).expect("Failed to connect to PostgreSQL");
+
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("Failed to set read timeout");
+
+    stream
+}
+
+fn connect(port: u16, user: &str, password: &str) -> Result<Connection<TcpStream>, Error> {
+    Connection::new(open_stream(port), user, password, "testing")
+}
+
+#[test]
+fn test_scram_authentication() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    // Create a user with a SCRAM-SHA-256 password verifier
+    temp_db.execute(
+        "
+        SET password_encryption = 'scram-sha-256';
+        CREATE USER scram_user WITH REPLICATION LOGIN PASSWORD 'scram secret';
+    ",
+    );
+
+    // Require SCRAM for that user, the postgres user keeps using trust
+    temp_db.prepend_hba_rule("host all scram_user all scram-sha-256");
+
+    // Correct password completes the SASL exchange
+    connect(temp_db.port, "scram_user", "scram secret")
+        .expect("Failed to authenticate with SCRAM-SHA-256");
+
+    // Wrong password is rejected by the server
+    let result = connect(temp_db.port, "scram_user", "wrong secret");
+    assert_eq!(
+        result.err().as_ref().and_then(Error::code),
+        Some("28P01"),
+        "Expected invalid password error"
+    );
+
+    // Channel binding cannot be required without TLS
+    let options = ConnectOptions::new()
+        .user("scram_user")
+        .password("scram secret")
+        .database("testing")
+        .channel_binding(ChannelBindingMode::Require);
+    let result = Connection::with_options(open_stream(temp_db.port), &options);
+    assert!(
+        matches!(result, Err(Error::Authentication(_))),
+        "Expected channel binding to be required"
+    );
+
+    // Requiring channel binding also rejects authentication methods that cannot bind
+    let options = ConnectOptions::new()
+        .user("postgres")
+        .database("testing")
+        .channel_binding(ChannelBindingMode::Require);
+    let result = Connection::with_options(open_stream(temp_db.port), &options);
+    assert!(
+        matches!(result, Err(Error::Authentication(_))),
+        "Expected trust authentication to be rejected"
+    );
+}
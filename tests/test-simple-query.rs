@@ -0,0 +1,73 @@
+mod common;
+
+use lolrepl::ConnectOptions;
+use lolrepl::NoTls;
+use lolrepl::Subscriber;
+use lolrepl::Value;
+
+#[test]
+fn test_simple_query() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+    ",
+    );
+
+    let mut connection = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing")
+        .connect(NoTls)
+        .expect("Failed to connect");
+
+    // Check the server configuration before replicating
+    let results = connection
+        .simple_query("SHOW wal_level")
+        .expect("Failed to show wal_level");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].command_tag, "SHOW");
+    assert_eq!(
+        results[0].rows,
+        vec![vec![Value::Text("logical".to_string())]]
+    );
+
+    // Values are typed by their column
+    let results = connection
+        .simple_query(
+            "SELECT slot_name, active, restart_lsn FROM pg_replication_slots WHERE slot_name = 'test_slot'",
+        )
+        .expect("Failed to query pg_replication_slots");
+    let result = &results[0];
+    assert_eq!(result.rows_affected(), Some(1));
+    assert_eq!(result.column_index("active"), Some(1));
+    assert_eq!(result.rows[0][0], Value::Text("test_slot".to_string()));
+    assert_eq!(result.rows[0][1], Value::Boolean(false));
+    assert!(matches!(result.rows[0][2], Value::Lsn(_)));
+
+    // Several statements give a result each, NULLs become Value::Null
+    let results = connection
+        .simple_query(
+            "INSERT INTO test_items (name) VALUES ('item1'), ('item2'); SELECT NULL::text, count(*) FROM test_items",
+        )
+        .expect("Failed to run statements");
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].command_tag, "INSERT 0 2");
+    assert!(results[0].columns.is_empty());
+    assert_eq!(results[1].rows, vec![vec![Value::Null, Value::BigInt(2)]]);
+
+    // Errors leave the connection usable for replication
+    let err = connection
+        .simple_query("SELECT * FROM missing_table")
+        .expect_err("Expected an error for a missing table");
+    assert_eq!(err.code(), Some("42P01"));
+
+    let mut sub = Subscriber::new(connection, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+    sub.next().expect("Failed to get replication message");
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use std::thread;
+use std::time::Duration;
+
+use lolrepl::AlterSlotOptions;
+use lolrepl::ConnectOptions;
+use lolrepl::CreateSlotOptions;
+use lolrepl::Error;
+use lolrepl::NoTls;
+use lolrepl::SlotType;
+use lolrepl::Subscriber;
+
+#[test]
+fn test_slot_commands() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+        SELECT pg_create_physical_replication_slot('physical_slot', true);
+    ",
+    );
+
+    let options = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing")
+        .read_timeout(Duration::from_secs(5));
+
+    let mut connection = options.connect(NoTls).expect("Failed to connect");
+    let server_version = connection.server_version_num().unwrap();
+
+    // Physical slots can be inspected, missing slots read as None
+    let info = connection
+        .read_replication_slot("physical_slot")
+        .expect("Failed to read slot")
+        .expect("Expected the physical slot to exist");
+    assert_eq!(info.slot_type, SlotType::Physical);
+    assert!(info.restart_lsn.is_some(), "Expected reserved WAL");
+    assert_eq!(info.restart_tli, Some(1));
+
+    let info = connection
+        .read_replication_slot("missing_slot")
+        .expect("Failed to read missing slot");
+    assert_eq!(info, None);
+
+    // Altering a slot needs PostgreSQL 17
+    connection
+        .create_replication_slot("test_slot", &CreateSlotOptions::new())
+        .expect("Failed to create slot");
+    let result =
+        connection.alter_replication_slot("test_slot", &AlterSlotOptions::new().failover(true));
+    if server_version >= 170000 {
+        result.expect("Failed to alter slot");
+    } else {
+        assert!(
+            matches!(result, Err(Error::UnsupportedServerVersion(_))),
+            "Expected unsupported ALTER_REPLICATION_SLOT"
+        );
+    }
+
+    // A slot that is streaming cannot be dropped without waiting
+    let streaming = options.connect(NoTls).expect("Failed to connect");
+    let sub = Subscriber::new(streaming, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    let result = connection.drop_replication_slot("test_slot", false);
+    assert!(
+        matches!(result, Err(Error::ReplicationSlotInUse(_))),
+        "Expected slot in use, got {:?}",
+        result
+    );
+    assert_eq!(result.unwrap_err().code(), Some("55006"));
+
+    // With WAIT the drop completes once the subscriber disconnects
+    let dropper = thread::spawn(move || {
+        connection
+            .drop_replication_slot("test_slot", true)
+            .map(|()| connection)
+    });
+    thread::sleep(Duration::from_millis(500));
+    drop(sub);
+    let mut connection = dropper
+        .join()
+        .expect("Drop thread panicked")
+        .expect("Failed to drop slot after waiting");
+
+    // The slot is gone now
+    let result = connection.drop_replication_slot("test_slot", false);
+    assert!(
+        matches!(result, Err(Error::ReplicationSlotDoesNotExist(_))),
+        "Expected missing slot, got {:?}",
+        result
+    );
+}
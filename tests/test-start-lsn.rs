@@ -0,0 +1,69 @@
+mod common;
+
+use std::time::Duration;
+
+use lolrepl::ConnectOptions;
+use lolrepl::Message;
+use lolrepl::NoTls;
+use lolrepl::Subscriber;
+use lolrepl::SubscriberOptions;
+use lolrepl::Value;
+
+#[test]
+fn test_start_lsn() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+        INSERT INTO test_items (name) VALUES ('skipped');
+    ",
+    );
+
+    let mut connection = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing")
+        .read_timeout(Duration::from_secs(5))
+        .connect(NoTls)
+        .expect("Failed to connect");
+
+    // Remember the position after the first transaction, as a sink would
+    let results = connection
+        .simple_query("SELECT pg_current_wal_lsn()")
+        .expect("Failed to get the current WAL location");
+    let Value::Lsn(start_lsn) = results[0].rows[0][0] else {
+        panic!("Expected a pg_lsn value");
+    };
+
+    temp_db.execute("INSERT INTO test_items (name) VALUES ('streamed');");
+
+    // Starting from the remembered position skips the first transaction
+    let options = SubscriberOptions::new("test_slot", "test_publication").start_lsn(start_lsn);
+    let mut sub =
+        Subscriber::with_options(connection, options).expect("Failed to create subscriber");
+
+    let mut messages = Vec::new();
+    for _ in 0..4 {
+        messages.push(sub.next().expect("Failed to get replication message"));
+    }
+
+    let Message::Begin { final_lsn, .. } = messages[0] else {
+        panic!("Expected Begin message, got {:?}", messages[0]);
+    };
+    assert!(
+        final_lsn >= start_lsn,
+        "Expected a transaction after the start LSN"
+    );
+    assert!(matches!(messages[1], Message::Relation { .. }));
+
+    let Message::Insert { ref tuple_data, .. } = messages[2] else {
+        panic!("Expected Insert message, got {:?}", messages[2]);
+    };
+    assert_eq!(tuple_data[1], Some(Value::Text("streamed".to_string())));
+    assert!(matches!(messages[3], Message::Commit { .. }));
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lolrepl::ConnectOptions;
+use lolrepl::Error;
+use lolrepl::Message;
+use lolrepl::NoTls;
+use lolrepl::Subscriber;
+use lolrepl::SubscriberOptions;
+
+// Send a signal to a server process
+fn signal(signal: &str, process_id: i32) {
+    let status = Command::new("kill")
+        .args([signal, &process_id.to_string()])
+        .status()
+        .expect("Failed to run kill");
+    assert!(
+        status.success(),
+        "Failed to send {} to {}",
+        signal,
+        process_id
+    );
+}
+
+#[test]
+fn test_stream_timeout() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE PUBLICATION test_publication FOR TABLE test_items;
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+    ",
+    );
+    temp_db.append_config("wal_sender_timeout = 4s");
+
+    // No read timeout, the subscriber sets its own
+    let connect_options = ConnectOptions::new()
+        .host("localhost")
+        .port(temp_db.port)
+        .user("postgres")
+        .database("testing");
+    let options = SubscriberOptions::new("test_slot", "test_publication")
+        .status_interval(Duration::from_secs(30))
+        .receive_timeout(Duration::from_secs(2));
+
+    // The status interval is limited to half of wal_sender_timeout
+    let connection = connect_options.connect(NoTls).expect("Failed to connect");
+    let cancel_token = connection.cancel_token();
+    let mut sub =
+        Subscriber::with_options(connection, options.clone()).expect("Failed to create subscriber");
+    assert_eq!(sub.status_interval(), Duration::from_secs(2));
+
+    // An idle server answers the reply requests, so the stream does not time out
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_secs(5));
+        cancel_token.cancel()
+    });
+    let err = sub.next().expect_err("Expected the stream to be canceled");
+    assert_eq!(err.code(), Some("57014"), "Unexpected error {:?}", err);
+    canceller
+        .join()
+        .expect("Cancel thread panicked")
+        .expect("Failed to send cancel request");
+    drop(sub);
+
+    // A server that stops responding is detected
+    let connection = connect_options.connect(NoTls).expect("Failed to connect");
+    let process_id = connection.process_id();
+    let mut sub =
+        Subscriber::with_options(connection, options).expect("Failed to create subscriber");
+
+    signal("-STOP", process_id);
+    let started = Instant::now();
+    let result = sub.next();
+    signal("-CONT", process_id);
+
+    assert!(
+        matches!(result, Err(Error::ReplicationStreamTimedOut)),
+        "Expected the stream to time out, got {:?}",
+        result
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+    drop(sub);
+
+    // A consumer that takes longer than the receive timeout still gets the waiting data,
+    // on a slot of its own so the change cannot reach the stream of the stopped server
+    temp_db.execute("SELECT pg_create_logical_replication_slot('busy_slot', 'pgoutput');");
+    let connection = connect_options.connect(NoTls).expect("Failed to connect");
+    let mut sub = Subscriber::with_options(
+        connection,
+        SubscriberOptions::new("busy_slot", "test_publication")
+            .receive_timeout(Duration::from_secs(2)),
+    )
+    .expect("Failed to create subscriber");
+
+    temp_db.execute("INSERT INTO test_items (name) VALUES ('item1');");
+    thread::sleep(Duration::from_secs(3));
+
+    let message = sub.next().expect("Expected the waiting data");
+    assert!(
+        matches!(message, Message::Begin { .. }),
+        "Expected Begin message, got {:?}",
+        message
+    );
+}
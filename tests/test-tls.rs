@@ -0,0 +1,179 @@
+#![cfg(feature = "native-tls")]
+
+mod common;
+
+use std::fs;
+use std::net::TcpStream;
+use std::os::unix::fs::PermissionsExt;
+use std::time::Duration;
+
+use openssl::asn1::Asn1Time;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::rsa::Rsa;
+use openssl::x509::X509;
+use openssl::x509::X509NameBuilder;
+use openssl::x509::extension::SubjectAlternativeName;
+
+use lolrepl::ChannelBindingMode;
+use lolrepl::ConnectOptions;
+use lolrepl::Connection;
+use lolrepl::Error;
+use lolrepl::NativeTlsConnector;
+use lolrepl::NoTls;
+use lolrepl::NoTlsStream;
+use lolrepl::Socket;
+use lolrepl::SslMode;
+use lolrepl::TlsConnect;
+use lolrepl::Value;
+
+// A TLS backend whose handshake always fails
+#[derive(Clone)]
+struct FailingTls;
+
+impl TlsConnect<Socket> for FailingTls {
+    type Stream = NoTlsStream;
+
+    fn connect(self, _stream: Socket, _domain: &str, _mode: SslMode) -> Result<NoTlsStream, Error> {
+        Err(Error::Tls("handshake failed".into()))
+    }
+}
+
+// Generate a self-signed certificate for localhost, returning PEM encoded certificate and key
+fn generate_certificate() -> (Vec<u8>, Vec<u8>) {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "localhost").unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+
+    let san = SubjectAlternativeName::new()
+        .dns("localhost")
+        .build(&builder.x509v3_context(None, None))
+        .unwrap();
+    builder.append_extension(san).unwrap();
+
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+
+    let certificate = builder.build().to_pem().unwrap();
+    let private_key = key.private_key_to_pem_pkcs8().unwrap();
+
+    (certificate, private_key)
+}
+
+fn open_stream(port: u16) -> TcpStream {
+    let stream =
+        TcpStream::connect(format!("localhost:{}", port)).expect("Failed to connect to PostgreSQL");
+
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("Failed to set read timeout");
+
+    stream
+}
+
+#[test]
+fn test_tls() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        SET password_encryption = 'scram-sha-256';
+        CREATE USER scram_user WITH REPLICATION LOGIN PASSWORD 'scram secret';
+    ",
+    );
+    temp_db.prepend_hba_rule("host all scram_user all scram-sha-256");
+
+    let options = ConnectOptions::new()
+        .host("localhost")
+        .user("scram_user")
+        .password("scram secret")
+        .database("testing")
+        .ssl_mode(SslMode::Require);
+
+    // The server does not accept TLS yet
+    let result = Connection::with_tls(
+        open_stream(temp_db.port),
+        &options,
+        NativeTlsConnector::new(),
+    );
+    assert!(
+        matches!(result, Err(Error::TlsNotSupported)),
+        "Expected server to refuse TLS"
+    );
+
+    // Enable TLS on the server with a self-signed certificate
+    let (certificate, private_key) = generate_certificate();
+    fs::write(temp_db.data_dir.join("server.crt"), &certificate).unwrap();
+    fs::write(temp_db.data_dir.join("server.key"), &private_key).unwrap();
+    fs::set_permissions(
+        temp_db.data_dir.join("server.key"),
+        fs::Permissions::from_mode(0o600),
+    )
+    .unwrap();
+    temp_db.append_config("ssl = on");
+
+    // A failed handshake falls back to plain text when TLS is only preferred
+    let prefer_options = options.clone().port(temp_db.port).ssl_mode(SslMode::Prefer);
+    let mut connection = prefer_options
+        .connect(FailingTls)
+        .expect("Failed to fall back to plain text");
+    let results = connection
+        .simple_query("SELECT ssl FROM pg_stat_ssl WHERE pid = pg_backend_pid()")
+        .expect("Failed to query pg_stat_ssl");
+    assert_eq!(results[0].rows, vec![vec![Value::Boolean(false)]]);
+
+    let result = prefer_options
+        .ssl_mode(SslMode::Require)
+        .connect(FailingTls);
+    assert!(
+        matches!(result, Err(Error::Tls(_))),
+        "Expected the failed handshake to fail the connection"
+    );
+
+    // Without a TLS backend, requiring TLS fails
+    let result = Connection::with_tls(open_stream(temp_db.port), &options, NoTls);
+    assert!(
+        matches!(result, Err(Error::Tls(_))),
+        "Expected missing TLS backend to fail"
+    );
+
+    // TLS without verification, with SCRAM bound to the TLS session
+    let options = options.channel_binding(ChannelBindingMode::Require);
+    Connection::with_tls(
+        open_stream(temp_db.port),
+        &options,
+        NativeTlsConnector::new(),
+    )
+    .expect("Failed to connect with TLS and channel binding");
+
+    // Full verification fails until the self-signed certificate is trusted
+    let options = options.ssl_mode(SslMode::VerifyFull);
+    let result = Connection::with_tls(
+        open_stream(temp_db.port),
+        &options,
+        NativeTlsConnector::new(),
+    );
+    assert!(
+        matches!(result, Err(Error::Tls(_))),
+        "Expected certificate verification to fail"
+    );
+
+    let tls = NativeTlsConnector::new()
+        .add_root_certificate(native_tls::Certificate::from_pem(&certificate).unwrap());
+    Connection::with_tls(open_stream(temp_db.port), &options, tls)
+        .expect("Failed to connect with a verified certificate");
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::net::TcpStream;
+use std::time::Duration;
+
+use lolrepl::Connection;
+use lolrepl::Message;
+use lolrepl::Subscriber;
+
+#[test]
+fn test_truncate() {
+    // Setup a temporary database
+    let temp_db = common::init_tmp_db();
+
+    temp_db.execute(
+        "
+        CREATE TABLE test_items (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+        CREATE TABLE test_tags (
+            item_id INTEGER REFERENCES test_items (id),
+            tag TEXT NOT NULL
+        );
+        CREATE TABLE test_notes (note TEXT NOT NULL);
+
+        CREATE PUBLICATION test_publication FOR TABLE test_items, test_tags, test_notes;
+
+        SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput');
+    ",
+    );
+
+    temp_db.execute(
+        "
+        INSERT INTO test_items (name) VALUES ('item1');
+        INSERT INTO test_tags (item_id, tag) VALUES (1, 'tag1');
+        INSERT INTO test_notes (note) VALUES ('note1');
+        TRUNCATE test_notes;
+        TRUNCATE test_items RESTART IDENTITY CASCADE;
+    ",
+    );
+
+    let stream = TcpStream::connect(format!("localhost:{}", temp_db.port))
+        .expect("Failed to connect to PostgreSQL");
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("Failed to set read timeout");
+
+    let conn = Connection::new(stream, "postgres", "", "testing")
+        .expect("Failed to create replication connection");
+
+    let mut sub = Subscriber::new(conn, "test_slot", "test_publication")
+        .expect("Failed to create subscriber");
+
+    // Skip the three inserts
+    for _ in 0..3 {
+        common::next_transaction(&mut sub);
+    }
+
+    // A plain truncate of a single table
+    let truncates: Vec<_> = common::next_transaction(&mut sub)
+        .into_iter()
+        .filter(|message| matches!(message, Message::Truncate { .. }))
+        .collect();
+    let [
+        Message::Truncate {
+            relation_ids,
+            cascade,
+            restart_identity,
+        },
+    ] = truncates.as_slice()
+    else {
+        panic!("Expected one Truncate message, got {:?}", truncates);
+    };
+    assert_eq!(relation_ids.len(), 1);
+    assert!(!cascade);
+    assert!(!restart_identity);
+
+    let notes = sub
+        .relation_info(relation_ids[0])
+        .expect("Expected relation");
+    assert_eq!(notes.name, "test_notes");
+
+    // The cascade also truncates the referencing table
+    let truncates: Vec<_> = common::next_transaction(&mut sub)
+        .into_iter()
+        .filter(|message| matches!(message, Message::Truncate { .. }))
+        .collect();
+    let [
+        Message::Truncate {
+            relation_ids,
+            cascade,
+            restart_identity,
+        },
+    ] = truncates.as_slice()
+    else {
+        panic!("Expected one Truncate message, got {:?}", truncates);
+    };
+    assert!(cascade);
+    assert!(restart_identity);
+
+    let mut names: Vec<_> = relation_ids
+        .iter()
+        .map(|id| {
+            sub.relation_info(*id)
+                .expect("Expected relation")
+                .name
+                .clone()
+        })
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["test_items", "test_tags"]);
+}